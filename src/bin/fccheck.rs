@@ -0,0 +1,45 @@
+use fileclass::core::config::Config;
+use fileclass::extra::args::take_flag;
+use fileclass::extra::input::{read_stdin_documents, with_library, LINE_DELIMITER, NUL_DELIMITER};
+
+use std::env;
+use std::process;
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let null = take_flag(&mut args, &["-0", "--null"]);
+    let delimiter = if null { NUL_DELIMITER } else { LINE_DELIMITER };
+
+    if !args.is_empty() {
+        panic!("Usage: fccheck [-0]");
+    }
+
+    let config = Config::std_load().expect("Can't load config");
+
+    for warning in config.warnings() {
+        eprintln!("Warning: {}", warning);
+    }
+
+    let library = &config.labels;
+    let mut violations = 0;
+
+    for document in with_library(read_stdin_documents(delimiter), library) {
+        let mut labels = document.labels.clone();
+        labels.expand_with(library);
+
+        for [a, b] in library.find_conflicts(&labels) {
+            println!(
+                "{}: \"{}\" conflicts with \"{}\"",
+                document.path.display(),
+                a,
+                b
+            );
+            violations += 1;
+        }
+    }
+
+    if violations > 0 {
+        eprintln!("{} conflict(s) found", violations);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,70 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use fileclass::core::config::settings::set_value;
+use fileclass::core::config::{
+    find_root, global_config_dir, Config, SETTINGS_FILENAME, STD_CONFIG_DIR,
+};
+use fileclass::extra::args::take_flag;
+
+const USAGE: &str = "Usage: fcconfig [set <key> <value> [--global]]";
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let global = take_flag(&mut args, &["--global"]);
+
+    match args.iter().map(|a| a.as_str()).collect::<Vec<_>>()[..] {
+        [] if !global => print_config(),
+        ["set", key, value] => set(key, value, global),
+        _ => panic!("{}", USAGE),
+    }
+}
+
+/// Prints the effective settings with the source of each value.
+fn print_config() {
+    let config = Config::std_load().expect("Can't load config");
+
+    for warning in config.warnings() {
+        eprintln!("Warning: {}", warning);
+    }
+
+    println!("# root: {}", config.root.display());
+
+    for (key, value, source) in config.settings.entries(&config.setting_sources) {
+        println!("{} = {} # {}", key, value, source);
+    }
+}
+
+fn set(key: &str, value: &str, global: bool) {
+    let dir_path = if global {
+        global_config_dir().expect("Can't determine the global config dir")
+    } else {
+        let current_dir = env::current_dir().unwrap();
+        match find_root(&current_dir) {
+            Some(root) => root.join(STD_CONFIG_DIR),
+            None => {
+                eprintln!(
+                    "No `{}` dir found, run fcinit or use --global",
+                    STD_CONFIG_DIR
+                );
+                process::exit(1);
+            }
+        }
+    };
+
+    let settings_path: PathBuf = dir_path.join(SETTINGS_FILENAME);
+    let content = fs::read_to_string(&settings_path).unwrap_or_default();
+
+    let content = match set_value(&content, key, value) {
+        Ok(content) => content,
+        Err(err) => {
+            eprintln!("Can't set \"{}\": {}", key, err);
+            process::exit(1);
+        }
+    };
+
+    fs::create_dir_all(&dir_path).expect("Can't create config dir");
+    fs::write(&settings_path, content).expect("Can't write settings");
+}
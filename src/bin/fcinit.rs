@@ -1,6 +1,7 @@
 use std::fs;
 use std::path::Path;
 
+use fileclass::core::config::settings::Settings;
 use fileclass::core::config::{LABELS_FILENAME, SETTINGS_FILENAME, STD_CONFIG_DIR};
 
 const LABELS_CONTENT: &str = r#"[label]
@@ -10,8 +11,6 @@ implies = ["implied"]
 
 [implied]"#;
 
-const SETTINGS_CONTENT: &str = r#"link_dir="fileclass/temp/links""#;
-
 fn main() {
     let folder_path = Path::new(STD_CONFIG_DIR);
     let labels_path = folder_path.join(LABELS_FILENAME);
@@ -29,6 +28,8 @@ fn main() {
 
     // Generate settings.toml if it doesn't exist
     if !settings_path.exists() {
-        fs::write(settings_path, SETTINGS_CONTENT).expect("Failed to generate settings.toml");
+        let settings_content =
+            toml::to_string(&Settings::default()).expect("Failed to serialize settings");
+        fs::write(settings_path, settings_content).expect("Failed to generate settings.toml");
     }
 }
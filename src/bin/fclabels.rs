@@ -1,65 +1,350 @@
+use serde::Serialize;
 use tabled::{
-    settings::{object::Rows, Modify, Style, Width},
+    settings::{format::Format, object::Segment, peaker::PriorityMax, Modify, Style, Width},
     Table, Tabled,
 };
+use terminal_size::{terminal_size, Width as TerminalWidth};
+
+use std::collections::{BTreeMap, HashSet};
+use std::env;
+use std::str::FromStr;
 
 use fileclass::core::config::Config;
-use fileclass::extra::input::read_stdin_documents;
+use fileclass::core::label::LabelLibrary;
+use fileclass::extra::args::{take_flag, take_value};
+use fileclass::extra::input::{read_stdin_documents, with_library, LINE_DELIMITER, NUL_DELIMITER};
+use fileclass::extra::output::escape_csv;
+use fileclass::extra::stats::LabelStats;
 
-#[derive(Tabled)]
+const USAGE: &str = "Usage: fclabels [--tree | --stats [--top N]] [--known | --unknown] [--used] \
+    [--format table|plain|json|csv|markdown | --json] [-0]";
+
+const DEFAULT_TOP: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum ListFormat {
+    /// Box drawn table fitting the width of the terminal.
+    #[default]
+    Table,
+    /// Names only, one per line.
+    Plain,
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl FromStr for ListFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(ListFormat::Table),
+            "plain" => Ok(ListFormat::Plain),
+            "json" => Ok(ListFormat::Json),
+            "csv" => Ok(ListFormat::Csv),
+            "markdown" => Ok(ListFormat::Markdown),
+            _ => Err(format!(
+                "Unknown format \"{}\", expected one of: table, plain, json, csv, markdown",
+                s
+            )),
+        }
+    }
+}
+
+fn display_lines(values: &[String]) -> String {
+    values.join("\n")
+}
+
+fn display_option(value: &Option<String>) -> String {
+    value.clone().unwrap_or_default()
+}
+
+#[derive(Tabled, Serialize)]
 struct Row {
     name: String,
-    aliases: String,
+    #[tabled(skip)]
+    known: bool,
+    #[tabled(display_with = "display_option")]
+    parent: Option<String>,
+    #[tabled(display_with = "display_lines")]
+    aliases: Vec<String>,
+    #[tabled(display_with = "display_lines")]
+    implies: Vec<String>,
+    #[tabled(rename = "implied by", display_with = "display_lines")]
+    implied_by: Vec<String>,
     description: String,
 }
 
-fn main() {
-    let config = Config::std_load().unwrap();
-    let library = config.labels;
+const CSV_HEADER: [&str; 7] = [
+    "name",
+    "known",
+    "parent",
+    "aliases",
+    "implies",
+    "implied_by",
+    "description",
+];
 
-    let mut rows: Vec<Row> = Vec::new();
-    let mut names = library.label_names();
-    names.sort();
-
-    for name in names.iter() {
+impl Row {
+    fn known(library: &LabelLibrary, name: &str) -> Self {
         let mut aliases = Vec::from(library.get_aliases(name));
         aliases.sort();
 
-        let aliases = aliases.join("\n");
-
-        rows.push(Row {
+        Self {
             name: name.to_string(),
+            known: true,
+            parent: library.get_parent(name).map(|p| p.to_string()),
             aliases,
+            implies: Vec::from(library.get_implies(name)),
+            implied_by: to_strings(library.implied_by(name)),
             description: library.get_description(name).to_string(),
-        });
+        }
     }
 
-    let mut unknown_labels: Vec<String> = read_stdin_documents()
-        .flat_map(|document| document.labels)
-        .filter(|label| !library.is_known(label))
-        .collect();
+    fn unknown(name: String, derived: bool) -> Self {
+        // Labels only ever added by file rules are marked as derived.
+        let description = if derived {
+            "Unknown label, derived by rules"
+        } else {
+            "Unknown label"
+        };
 
-    unknown_labels.sort();
-    unknown_labels.dedup(); // Works thanks to the sort.
+        Self {
+            name,
+            known: false,
+            parent: None,
+            aliases: Vec::new(),
+            implies: Vec::new(),
+            implied_by: Vec::new(),
+            description: description.to_string(),
+        }
+    }
 
-    for label in unknown_labels.iter() {
-        rows.push(Row {
-            name: label.to_string(),
-            aliases: "".to_string(),
-            description: "Unknown label".to_string(),
-        });
+    fn csv_fields(&self) -> [String; 7] {
+        [
+            self.name.clone(),
+            self.known.to_string(),
+            display_option(&self.parent),
+            self.aliases.join(" "),
+            self.implies.join(" "),
+            self.implied_by.join(" "),
+            self.description.clone(),
+        ]
     }
+}
 
-    let mut table = Table::new(rows);
+fn to_strings(strs: Vec<&str>) -> Vec<String> {
+    strs.into_iter().map(|s| s.to_string()).collect()
+}
 
-    // TODO: Use percents per col instead of fixed values.
+/// Width of the terminal, if the output is one.
+fn terminal_width() -> Option<usize> {
+    terminal_size().map(|(TerminalWidth(width), _)| width as usize)
+}
 
+/// Applies the style of the table, shrinking the widest columns first to fit
+/// the terminal.
+fn styled(mut table: Table) -> Table {
     table.with(Style::modern());
-    table.with(Modify::new(Rows::first()).with(Width::wrap(25).keep_words()));
-    table.with(Modify::new(Rows::new(1..=1)).with(Width::wrap(25).keep_words()));
-    table.with(Modify::new(Rows::new(2..)).with(Width::wrap(100).keep_words()));
 
-    // table.with(Width::wrap(Percent(90)));
+    if let Some(width) = terminal_width() {
+        table.with(Width::wrap(width).keep_words().priority::<PriorityMax>());
+    }
+
+    table
+}
+
+/// Applies the markdown style, breaking lines with `<br>` since a newline
+/// would end the row.
+fn markdown(mut table: Table) -> Table {
+    table
+        .with(Style::markdown())
+        .with(Modify::new(Segment::all()).with(Format::content(|s| s.replace('\n', "<br>"))));
+
+    table
+}
+
+fn print_rows(rows: Vec<Row>, format: ListFormat) {
+    match format {
+        ListFormat::Table => println!("{}", styled(Table::new(rows))),
+        ListFormat::Markdown => println!("{}", markdown(Table::new(rows))),
+        ListFormat::Plain => {
+            for row in rows {
+                println!("{}", row.name);
+            }
+        }
+        ListFormat::Json => println!("{}", serde_json::to_string_pretty(&rows).unwrap()),
+        ListFormat::Csv => {
+            println!("{}", CSV_HEADER.join(","));
+            for row in rows {
+                let fields: Vec<String> = row.csv_fields().iter().map(|f| escape_csv(f)).collect();
+                println!("{}", fields.join(","));
+            }
+        }
+    }
+}
+
+/// Prints the labels below `name` as a tree, using `prefix` for the lines
+/// of the ancestors.
+fn print_children(library: &LabelLibrary, name: &str, prefix: &str) {
+    let children = library.children(name);
+
+    for (i, child) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        let (branch, indent) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+
+        println!("{}{}{}", prefix, branch, child);
+        print_children(library, child, &format!("{}{}", prefix, indent));
+    }
+}
+
+fn print_tree(library: &LabelLibrary) {
+    for root in library.roots() {
+        println!("{}", root);
+        print_children(library, root, "");
+    }
+}
+
+#[derive(Tabled)]
+struct CountRow {
+    label: String,
+    explicit: usize,
+    implied: usize,
+    known: String,
+}
+
+#[derive(Tabled)]
+struct CooccurrenceRow {
+    labels: String,
+    count: usize,
+}
+
+fn print_stats(stats: &LabelStats) {
+    println!("{} document(s)", stats.documents);
+
+    let rows = stats.labels.iter().map(|c| CountRow {
+        label: c.label.clone(),
+        explicit: c.explicit,
+        implied: c.implied,
+        known: if c.known { "yes" } else { "no" }.to_string(),
+    });
+    println!("{}", styled(Table::new(rows)));
+
+    if !stats.unused.is_empty() {
+        println!("Unused labels: {}", stats.unused.join(", "));
+    }
+
+    if !stats.cooccurrences.is_empty() {
+        let rows = stats.cooccurrences.iter().map(|c| CooccurrenceRow {
+            labels: c.labels.join(" + "),
+            count: c.count,
+        });
+        println!("{}", styled(Table::new(rows)));
+    }
+}
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let tree = take_flag(&mut args, &["--tree"]);
+    let stats = take_flag(&mut args, &["--stats"]);
+    let top = take_value(&mut args, &["--top"]).map(|v| v.parse().expect("Invalid top"));
+    let known_only = take_flag(&mut args, &["--known"]);
+    let unknown_only = take_flag(&mut args, &["--unknown"]);
+    let used_only = take_flag(&mut args, &["--used"]);
+    let format: Option<ListFormat> = take_value(&mut args, &["--format"])
+        .map(|v| v.parse().unwrap_or_else(|err| panic!("{}", err)));
+    // Kept from before `--format`, same as `--format json`.
+    let json = take_flag(&mut args, &["--json"]);
+    let null = take_flag(&mut args, &["-0", "--null"]);
+    let delimiter = if null { NUL_DELIMITER } else { LINE_DELIMITER };
+
+    let filtered = known_only || unknown_only || used_only;
+    let stats_format = matches!(format, None | Some(ListFormat::Table | ListFormat::Json));
+
+    if !args.is_empty()
+        || (tree && (stats || filtered || format.is_some() || json))
+        || (stats && (filtered || !stats_format))
+        || (!stats && top.is_some())
+        || (known_only && unknown_only)
+        || (json && format.is_some_and(|f| f != ListFormat::Json))
+    {
+        panic!("{}", USAGE);
+    }
+
+    let format = if json {
+        ListFormat::Json
+    } else {
+        format.unwrap_or_default()
+    };
+    let config = Config::std_load().unwrap();
+
+    for warning in config.warnings() {
+        eprintln!("Warning: {}", warning);
+    }
+
+    let library = config.labels;
+
+    if tree {
+        print_tree(&library);
+        return;
+    }
+
+    if stats {
+        let documents = with_library(read_stdin_documents(delimiter), &library);
+        let stats = LabelStats::collect(documents, &library, top.unwrap_or(DEFAULT_TOP));
+
+        if format == ListFormat::Json {
+            println!("{}", serde_json::to_string_pretty(&stats).unwrap());
+        } else {
+            print_stats(&stats);
+        }
+
+        return;
+    }
+
+    // Labels in use, known ones by their library name.
+    let mut used: HashSet<String> = HashSet::new();
+    let mut unknown_labels: BTreeMap<String, bool> = BTreeMap::new();
+
+    // Known labels don't depend on the documents unless filtered by use.
+    if !known_only || used_only {
+        for document in with_library(read_stdin_documents(delimiter), &library) {
+            for label in document.labels.iter() {
+                if !library.is_known(label) {
+                    let derived = document.derived.inner_set().contains(label);
+                    *unknown_labels.entry(label.clone()).or_insert(true) &= derived;
+                }
+            }
+
+            let mut expanded = document.labels.clone();
+            expanded.expand_with(&library);
+            used.extend(expanded.iter().map(|l| library.resolve(l).to_string()));
+        }
+    }
+
+    let mut rows: Vec<Row> = Vec::new();
+
+    if !unknown_only {
+        let mut names = library.label_names();
+        names.sort();
+
+        for name in names {
+            if !used_only || used.contains(name) {
+                rows.push(Row::known(&library, name));
+            }
+        }
+    }
+
+    // Unknown labels always come from the documents, so they are in use.
+    if !known_only {
+        for (label, derived) in unknown_labels {
+            rows.push(Row::unknown(label, derived));
+        }
+    }
 
-    println!("{}", table.to_string());
+    print_rows(rows, format);
 }
@@ -3,17 +3,39 @@
 // TODO: Improve.
 // TODO: Support dirs.
 
+use std::env;
 use std::fs;
+use std::io::{self, Write};
 use std::path::Path;
 use std::process;
 
 use fileclass::core::config::Config;
-use fileclass::extra::input::{map_stdin_sources_to_target_folder, SourceTargetPair};
+use fileclass::extra::args::take_flag;
+use fileclass::extra::input::{
+    map_stdin_sources_to_target_folder, SourceTargetPair, LINE_DELIMITER, NUL_DELIMITER,
+};
+use fileclass::utils::fs::path_to_bytes;
 
 fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let null = take_flag(&mut args, &["-0", "--null"]);
+    let delimiter = if null { NUL_DELIMITER } else { LINE_DELIMITER };
+
+    if !args.is_empty() {
+        panic!("Usage: fclink [-0]");
+    }
+
     let config = Config::std_load().unwrap();
+
+    for warning in config.warnings() {
+        eprintln!("Warning: {}", warning);
+    }
+
     let link_dir = config.settings.link_dir;
 
+    // Input paths and the link dir are relative to the config root.
+    env::set_current_dir(&config.root).unwrap();
+
     // Get the target folder path
     let target_folder = Path::new(&link_dir);
 
@@ -25,17 +47,20 @@ fn main() {
     // Create the target folder
     fs::create_dir_all(target_folder).unwrap();
 
-    map_stdin_sources_to_target_folder(target_folder.to_path_buf()).for_each(|p| {
+    let mut stdout = io::stdout();
+
+    map_stdin_sources_to_target_folder(target_folder.to_path_buf(), delimiter).for_each(|p| {
         let SourceTargetPair { source, target } = p;
 
-        println!("{}", target.to_str().unwrap());
+        stdout.write_all(&path_to_bytes(&target)).unwrap();
+        stdout.write_all(&[delimiter]).unwrap();
 
         // Temporal safe guard for directories and other entities.
         // TODO: Support directories at least.
         if !Path::new(&source).is_file() {
             eprintln!(
                 "Warning: \"{}\" is not a regular file, ignoring.",
-                source.to_str().unwrap()
+                source.display()
             );
 
             return;
@@ -45,7 +70,7 @@ fn main() {
         if let Err(err) = fs::hard_link(&source, &target) {
             eprintln!(
                 "Failed to create hard link for \"{}\": {}",
-                source.to_str().unwrap(),
+                source.display(),
                 err
             );
             process::exit(1);
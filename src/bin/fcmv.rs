@@ -1,17 +1,29 @@
-use fileclass::extra::input::{map_stdin_sources_to_target_folder, SourceTargetPair};
-use std::{env, fs, path::Path, process};
+use fileclass::core::config::find_root;
+use fileclass::extra::args::take_flag;
+use fileclass::extra::input::{
+    map_stdin_sources_to_target_folder, SourceTargetPair, LINE_DELIMITER, NUL_DELIMITER,
+};
+use std::{env, fs, process};
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    let null = take_flag(&mut args, &["-0", "--null"]);
+    let delimiter = if null { NUL_DELIMITER } else { LINE_DELIMITER };
 
     if args.len() != 2 {
-        panic!("Usage: fcmv <target_folder>");
+        panic!("Usage: fcmv [-0] <target_folder>");
     }
 
-    let target_folder = Path::new(&args[1]);
-    fs::create_dir_all(target_folder).expect("Can't create target folder");
+    // The target folder is given relative to where the user is, but input paths
+    // are relative to the config root.
+    let current_dir = env::current_dir().unwrap();
+    let target_folder = current_dir.join(&args[1]);
+    let root = find_root(&current_dir).unwrap_or(current_dir);
+    env::set_current_dir(root).unwrap();
 
-    map_stdin_sources_to_target_folder(target_folder.to_path_buf()).for_each(|p| {
+    fs::create_dir_all(&target_folder).expect("Can't create target folder");
+
+    map_stdin_sources_to_target_folder(target_folder, delimiter).for_each(|p| {
         let SourceTargetPair { source, target } = p;
 
         if let Err(err) = fs::rename(&source, &target) {
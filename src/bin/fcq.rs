@@ -1,32 +1,171 @@
 use fileclass::core::{
     config::Config,
-    query::{check, CheckParams},
+    document::Document,
+    label::LabelLibrary,
+    query::{
+        check,
+        explain::explain,
+        rank::{fuzzy_match, rank, RankOptions},
+        validate::{find_issues, QueryIssue},
+        CheckParams,
+    },
 };
 
-use fileclass::extra::input::read_stdin_documents;
+use fileclass::extra::args::{take_flag, take_value};
+use fileclass::extra::input::{read_stdin_documents, with_library, LINE_DELIMITER, NUL_DELIMITER};
+use fileclass::extra::listing::{apply_listing, ListingOptions};
+use fileclass::extra::output::{DocumentRecord, DocumentWriter, Format};
 
+use std::borrow::Borrow;
 use std::env;
+use std::io;
+use std::process;
+
+const USAGE: &str = "Usage: fcq [--strict] [--explain] [--rank [--fuzzy]] [-0] [--format FORMAT] \
+    [--sort name|path|label-count|mtime] [--reverse] [--unique-name] [--offset N] [--limit N] <query>";
+
+fn report_issues(issues: Vec<QueryIssue>, strict: bool) {
+    for issue in issues.iter() {
+        if strict {
+            eprintln!("Error: {}", issue);
+        } else {
+            eprintln!("Warning: {}", issue);
+        }
+    }
+
+    if strict && !issues.is_empty() {
+        process::exit(1);
+    }
+}
+
+struct Output<'a> {
+    prompt: &'a str,
+    library: &'a LabelLibrary,
+    format: Format,
+    explained: bool,
+}
+
+impl Output<'_> {
+    fn print<D: Borrow<Document>>(&self, documents: impl Iterator<Item = D>) {
+        let mut writer = DocumentWriter::new(io::stdout(), self.format);
+
+        for document in documents {
+            let document = document.borrow();
+
+            writer
+                .write(&DocumentRecord::new(document, self.library))
+                .expect("Can't write output");
+
+            if self.explained {
+                let params = CheckParams {
+                    prompt: self.prompt,
+                    document,
+                    library: self.library,
+                };
+
+                for term in explain(&params).terms {
+                    println!("  {}", term);
+                }
+            }
+        }
+
+        writer.finish().expect("Can't write output");
+    }
+}
 
 // TODO: Handle errors here.
 fn main() {
     let config = Config::std_load().expect("Can't load config");
-    let args: Vec<String> = env::args().collect();
 
-    if args.len() < 2 {
-        panic!("Usage: fileclass <query>");
+    for warning in config.warnings() {
+        eprintln!("Warning: {}", warning);
+    }
+
+    // Input paths are relative to the config root, metadata matchers need it.
+    env::set_current_dir(&config.root).expect("Can't enter the config root");
+
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let strict = take_flag(&mut args, &["--strict"]);
+    let explained = take_flag(&mut args, &["--explain"]);
+    let ranked = take_flag(&mut args, &["--rank"]);
+    let fuzzy_name = take_flag(&mut args, &["--fuzzy"]);
+    let null = take_flag(&mut args, &["-0", "--null"]);
+
+    // With `-0`, paths are read and written separated by NUL unless another
+    // format is asked for.
+    let delimiter = if null { NUL_DELIMITER } else { LINE_DELIMITER };
+    let format = Format::from_args(take_value(&mut args, &["--format"]).as_deref(), null)
+        .unwrap_or_else(|err| panic!("{}", err));
+
+    let listing = ListingOptions {
+        sort: take_value(&mut args, &["--sort"])
+            .map(|v| v.parse().unwrap_or_else(|err| panic!("{}", err))),
+        reverse: take_flag(&mut args, &["--reverse"]),
+        unique_name: take_flag(&mut args, &["--unique-name"]),
+        offset: take_value(&mut args, &["--offset"])
+            .map(|v| v.parse().expect("Invalid offset"))
+            .unwrap_or(0),
+        limit: take_value(&mut args, &["--limit"]).map(|v| v.parse().expect("Invalid limit")),
+    };
+
+    // Explanations are only readable next to plain paths.
+    if args.is_empty() || (fuzzy_name && !ranked) || (explained && format != Format::Lines) {
+        panic!("{}", USAGE);
     }
 
-    let prompt = &args[1..].join(" ");
+    let prompt = &args.join(" ");
+    let library = &config.labels;
+
+    let documents = with_library(read_stdin_documents(delimiter), library);
+
+    // Terms unknown to the library may still be labels of the input, which is
+    // then read upfront to tell. Otherwise documents are streamed.
+    let documents: Box<dyn Iterator<Item = Document>> =
+        if find_issues(prompt, library, &[]).is_empty() {
+            Box::new(documents)
+        } else {
+            let documents: Vec<Document> = documents.collect();
+            let mut issues = find_issues(prompt, library, &documents);
+
+            // Unknown labels may be words of the names when fuzzy matching them.
+            if fuzzy_name {
+                issues.retain(|issue| match issue {
+                    QueryIssue::UnknownLabel { label, .. } => {
+                        !documents.iter().any(|d| fuzzy_match(&d.name, label))
+                    }
+                    _ => true,
+                });
+            }
 
-    let result = read_stdin_documents().filter(|d| {
-        let params = CheckParams {
-            prompt: &prompt,
-            document: &d,
-            library: &config.labels,
+            report_issues(issues, strict);
+            Box::new(documents.into_iter())
         };
 
-        check(&params)
-    });
+    let output = Output {
+        prompt,
+        library,
+        format,
+        explained,
+    };
 
-    result.for_each(|d| println!("{}", d.path));
+    if ranked {
+        let documents: Vec<Document> = documents.collect();
+        let options = RankOptions { fuzzy_name };
+        let ranked = rank(&documents, prompt, library, &options)
+            .into_iter()
+            .map(|(document, _)| document);
+
+        output.print(apply_listing(ranked, &listing));
+    } else {
+        let matching = documents.filter(|document| {
+            check(&CheckParams {
+                prompt,
+                document,
+                library,
+            })
+        });
+
+        // Without `--sort`, documents keep the input order.
+        output.print(apply_listing(matching, &listing));
+    }
 }
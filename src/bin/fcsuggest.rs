@@ -0,0 +1,99 @@
+use fileclass::core::config::Config;
+use fileclass::core::document::Document;
+use fileclass::extra::args::{take_flag, take_value};
+use fileclass::extra::input::{read_stdin_documents, with_library, LINE_DELIMITER, NUL_DELIMITER};
+use fileclass::extra::suggest::{with_labels, Model, SuggestOptions};
+use fileclass::utils::fs::path_to_bytes;
+
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::process;
+
+const USAGE: &str = "Usage: fcsuggest [--min-confidence N] [--limit N] [--apply] [-0]";
+
+const DEFAULT_MIN_CONFIDENCE: f64 = 0.5;
+const DEFAULT_LIMIT: usize = 3;
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let apply = take_flag(&mut args, &["--apply"]);
+    let null = take_flag(&mut args, &["-0", "--null"]);
+    let delimiter = if null { NUL_DELIMITER } else { LINE_DELIMITER };
+
+    let options = SuggestOptions {
+        min_confidence: take_value(&mut args, &["--min-confidence"])
+            .map(|v| v.parse().expect("Invalid minimum confidence"))
+            .unwrap_or(DEFAULT_MIN_CONFIDENCE),
+        limit: take_value(&mut args, &["--limit"])
+            .map(|v| v.parse().expect("Invalid limit"))
+            .unwrap_or(DEFAULT_LIMIT),
+    };
+
+    if !args.is_empty() {
+        panic!("{}", USAGE);
+    }
+
+    let config = Config::std_load().expect("Can't load config");
+
+    for warning in config.warnings() {
+        eprintln!("Warning: {}", warning);
+    }
+
+    // Input paths are relative to the config root, renames need it.
+    env::set_current_dir(&config.root).expect("Can't enter the config root");
+
+    let library = &config.labels;
+
+    // Every document is part of the corpus the suggestions are learned from.
+    let documents: Vec<Document> = with_library(read_stdin_documents(delimiter), library).collect();
+    let model = Model::train(&documents, library);
+    let mut stdout = io::stdout();
+
+    for document in documents.iter() {
+        let suggestions = model.suggest(document, library, &options);
+
+        if suggestions.is_empty() {
+            continue;
+        }
+
+        // Paths are written as they are, ended like the input ones.
+        stdout.write_all(&path_to_bytes(&document.path)).unwrap();
+        stdout.write_all(&[delimiter]).unwrap();
+        for suggestion in suggestions.iter() {
+            writeln!(
+                stdout,
+                "  {} {:.2}",
+                suggestion.label, suggestion.confidence
+            )
+            .unwrap();
+        }
+
+        if !apply {
+            continue;
+        }
+
+        let labels: Vec<&str> = suggestions.iter().map(|s| s.label.as_str()).collect();
+
+        let target = match with_labels(&document.path, &labels) {
+            Some(target) => target,
+            None => {
+                eprintln!(
+                    "Warning: Can't add labels to non UTF-8 filename {}",
+                    document.path.display()
+                );
+                continue;
+            }
+        };
+
+        if target.exists() {
+            eprintln!("Warning: {} already exists", target.display());
+            continue;
+        }
+
+        if let Err(err) = fs::rename(&document.path, &target) {
+            eprintln!("Failed to move file: {}", err);
+            process::exit(1);
+        }
+    }
+}
@@ -1,38 +1,87 @@
-// TODO: Add a way to ignore certain directories. Specially the `fileclass` dir.
+// TODO: Add a way to ignore certain directories besides the `fileclass` dir.
 
 use std::env;
-use std::fs;
+use std::io;
+use std::path::PathBuf;
 
-use fileclass::core::config::STD_CONFIG_DIR;
+use fileclass::core::config::{find_root, Config, STD_CONFIG_DIR};
+use fileclass::core::document::Document;
+use fileclass::core::label::LabelLibrary;
+use fileclass::extra::args::{take_flag, take_value};
+use fileclass::extra::output::{DocumentRecord, DocumentWriter, Format};
+use fileclass::extra::walk::{walk, WalkOptions};
+
+const USAGE: &str = "Usage: fcwalk [--max-depth N] [-L|--follow-links] [-x|--one-file-system] [--threads N] [--sorted] [-0] [--format FORMAT]";
 
 fn main() {
-    // Get the current directory
+    let mut args: Vec<String> = env::args().skip(1).collect();
+
+    let max_depth = take_value(&mut args, &["--max-depth"])
+        .map(|v| v.parse().expect("Invalid value for --max-depth"));
+    let threads = take_value(&mut args, &["--threads"])
+        .map(|v| v.parse().expect("Invalid value for --threads"))
+        .unwrap_or(0);
+    let follow_links = take_flag(&mut args, &["-L", "--follow-links"]);
+    let same_file_system = take_flag(&mut args, &["-x", "--one-file-system"]);
+    let sorted = take_flag(&mut args, &["--sorted"]);
+    let null = take_flag(&mut args, &["-0", "--null"]);
+    // An explicit format wins over `-0`, like in fcq.
+    let format = Format::from_args(take_value(&mut args, &["--format"]).as_deref(), null)
+        .unwrap_or_else(|err| panic!("{}", err));
+
+    if !args.is_empty() {
+        panic!("{}", USAGE);
+    }
+
+    let options = WalkOptions {
+        max_depth,
+        follow_links,
+        same_file_system,
+        // Omit the fileclass directory.
+        exclude_names: vec![STD_CONFIG_DIR.into()],
+        threads,
+    };
+
+    // Only the current dir is walked but paths are printed relative to the
+    // config root, so the output is the same no matter where this is run from.
     let current_dir = env::current_dir().unwrap();
+    let root = find_root(&current_dir).unwrap_or_else(|| current_dir.clone());
 
-    // Recursively traverse the directory tree
-    traverse_directory(&current_dir, &current_dir);
-}
+    // The config is only needed to expand labels.
+    let library = if format.has_labels() {
+        let config = Config::std_load().expect("Can't load config");
 
-fn traverse_directory(path: &std::path::Path, base_path: &std::path::Path) {
-    // Read the directory entries
-    if let Ok(entries) = fs::read_dir(path) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let entry_path = entry.path();
-
-                // Omit the fileclass directory.
-                if !entry_path.ends_with(STD_CONFIG_DIR) {
-                    let relative_path = entry_path.strip_prefix(base_path).unwrap();
-
-                    // Print the relative entry path
-                    println!("{}", relative_path.display());
-
-                    // Recursively traverse subdirectories
-                    if entry_path.is_dir() {
-                        traverse_directory(&entry_path, base_path);
-                    }
-                }
-            }
+        for warning in config.warnings() {
+            eprintln!("Warning: {}", warning);
         }
+
+        config.labels
+    } else {
+        LabelLibrary::empty()
+    };
+
+    let mut writer = DocumentWriter::new(io::stdout(), format);
+
+    // Directories are walked in parallel, so paths come in no particular order
+    // unless the whole walk is buffered and sorted.
+    let paths: Box<dyn Iterator<Item = PathBuf>> = if sorted {
+        let mut paths: Vec<PathBuf> = walk(&current_dir, options).collect();
+        paths.sort();
+        Box::new(paths.into_iter())
+    } else {
+        Box::new(walk(&current_dir, options))
+    };
+
+    for entry_path in paths {
+        let relative_path = entry_path.strip_prefix(&root).unwrap();
+
+        let mut document = Document::from_path(relative_path);
+        document.prepare(&library);
+
+        writer
+            .write(&DocumentRecord::new(&document, &library))
+            .expect("Can't write output");
     }
+
+    writer.finish().expect("Can't write output");
 }
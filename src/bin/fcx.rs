@@ -1,9 +1,18 @@
 use std::env;
-use std::io::{self, BufRead};
+use std::io::{self, Write};
 use std::process::{Command, Stdio};
 
+use fileclass::extra::args::take_value;
+use fileclass::extra::input::{read_paths, NUL_DELIMITER};
+use fileclass::utils::fs::path_to_bytes;
+
 fn main() {
-    let args: Vec<String> = env::args().skip(1).collect();
+    let mut args: Vec<String> = env::args().skip(1).collect();
+
+    // The output of fcq is read by fclink, so it must stay paths.
+    if take_value(&mut args, &["--format"]).is_some() {
+        panic!("fcx doesn't support --format, its output is always the links");
+    }
     /*
         if args.is_empty() {
             Command::new("fcinit")
@@ -14,7 +23,9 @@ fn main() {
             return;
         }
     */
+    // Paths are passed around separated by NUL, so any name is supported.
     let fcwalk_output = Command::new("fcwalk")
+        .arg("-0")
         .stdout(Stdio::piped())
         .spawn()
         .expect("Failed to execute fcwalk")
@@ -22,6 +33,7 @@ fn main() {
         .expect("Failed to capture fcwalk output");
 
     let fcq_output = Command::new("fcq")
+        .arg("-0")
         .args(&args)
         .stdin(fcwalk_output)
         .stdout(Stdio::piped())
@@ -31,6 +43,7 @@ fn main() {
         .expect("Failed to capture fcq output");
 
     let fclink_output = Command::new("fclink")
+        .arg("-0")
         .stdin(fcq_output)
         .stdout(Stdio::piped())
         .spawn()
@@ -39,9 +52,10 @@ fn main() {
         .expect("Failed to capture fclink output");
 
     let reader = io::BufReader::new(fclink_output);
-    for line in reader.lines() {
-        if let Ok(link_path) = line {
-            println!("{}", link_path);
-        }
+    let mut stdout = io::stdout();
+
+    for link_path in read_paths(reader, NUL_DELIMITER) {
+        stdout.write_all(&path_to_bytes(&link_path)).unwrap();
+        stdout.write_all(b"\n").unwrap();
     }
 }
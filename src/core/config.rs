@@ -1,9 +1,13 @@
-use serde::Deserialize;
+use std::env;
 use std::error::Error;
-use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use super::label::LabelLibrary;
+use include::{load_labels, LabelConflict};
+use settings::{SettingSources, Settings, SettingsLayers};
+
+pub mod include;
+pub mod settings;
 
 pub const STD_CONFIG_DIR: &str = "fileclass";
 pub const LABELS_FILENAME: &str = "labels.toml";
@@ -12,35 +16,137 @@ pub const SETTINGS_FILENAME: &str = "settings.toml";
 pub struct Config {
     pub labels: LabelLibrary,
     pub settings: Settings,
-}
-
-#[derive(Deserialize)]
-pub struct Settings {
-    // TODO: Use a default if missing and use that default in fcinit.
-    pub link_dir: String,
+    pub setting_sources: SettingSources,
+    /// Directory containing the config dir. Document paths are relative to it.
+    pub root: PathBuf,
+    /// Problems found while combining included label files.
+    pub conflicts: Vec<LabelConflict>,
+    /// Problems found while combining settings files.
+    pub settings_warnings: Vec<String>,
 }
 
 impl Config {
     // TODO: Remove file system dependency from core.
     pub fn load(dir_path: &str) -> Result<Self, Box<dyn Error>> {
         let labels_path = Path::new(dir_path).join(LABELS_FILENAME);
-        let labels_content = fs::read_to_string(labels_path)?;
-        let labels = LabelLibrary::from_toml(&labels_content)?;
+        let (labels, conflicts) = load_labels(&labels_path)?;
 
         let settings_path = Path::new(dir_path).join(SETTINGS_FILENAME);
-        let settings_content = fs::read_to_string(settings_path)?;
-        let settings: Settings = toml::from_str(&settings_content)?;
+        let mut settings_layers = SettingsLayers::new();
+        settings_layers.add_file(&settings_path)?;
+        let (settings, setting_sources, settings_warnings) = settings_layers.build()?;
+        let labels = labels.normalized(settings.normalization())?;
 
-        let config = Config { labels, settings };
+        let root = Path::new(dir_path)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        let config = Config {
+            labels,
+            settings,
+            setting_sources,
+            root,
+            conflicts,
+            settings_warnings,
+        };
 
         Ok(config)
     }
 
+    /// Loads and combines the config files found in `dir_paths`, in order.
+    ///
+    /// Every file is optional. Labels and settings from later dirs are merged
+    /// over the previous ones. Without any file, labels are empty and settings
+    /// are the defaults.
+    pub fn load_layers(root: PathBuf, dir_paths: &[PathBuf]) -> Result<Self, Box<dyn Error>> {
+        let mut labels = LabelLibrary::empty();
+        let mut settings_layers = SettingsLayers::new();
+        let mut conflicts = Vec::new();
+
+        for dir_path in dir_paths {
+            let labels_path = dir_path.join(LABELS_FILENAME);
+            if labels_path.is_file() {
+                let (layer_labels, layer_conflicts) = load_labels(&labels_path)?;
+                labels = labels.merge(layer_labels)?;
+                conflicts.extend(layer_conflicts);
+            }
+
+            let settings_path = dir_path.join(SETTINGS_FILENAME);
+            if settings_path.is_file() {
+                settings_layers.add_file(&settings_path)?;
+            }
+        }
+
+        let (settings, setting_sources, settings_warnings) = settings_layers.build()?;
+
+        // Labels are normalized once merged, since the settings may come from
+        // a different layer.
+        let labels = labels.normalized(settings.normalization())?;
+
+        let config = Config {
+            labels,
+            settings,
+            setting_sources,
+            root,
+            conflicts,
+            settings_warnings,
+        };
+
+        Ok(config)
+    }
+
+    /// Human readable problems found while loading that don't prevent using
+    /// the config.
+    pub fn warnings(&self) -> Vec<String> {
+        self.conflicts
+            .iter()
+            .map(|c| c.to_string())
+            .chain(self.settings_warnings.iter().cloned())
+            .collect()
+    }
+
+    /// Loads the config of the closest root containing the current directory,
+    /// layered over the global config.
+    ///
+    /// If there is no root, the current directory is used as root and only the
+    /// global config applies.
     pub fn std_load() -> Result<Self, Box<dyn Error>> {
-        Config::load(STD_CONFIG_DIR)
+        let current_dir = env::current_dir()?;
+
+        let (root, local_dir) = match find_root(&current_dir) {
+            Some(root) => {
+                let local_dir = root.join(STD_CONFIG_DIR);
+                (root, Some(local_dir))
+            }
+            None => (current_dir, None),
+        };
+
+        let dir_paths: Vec<PathBuf> = global_config_dir().into_iter().chain(local_dir).collect();
+
+        Config::load_layers(root, &dir_paths)
     }
 }
 
+/// Returns the user level config dir, following the XDG base directory spec.
+pub fn global_config_dir() -> Option<PathBuf> {
+    let config_home = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+
+    Some(config_home.join(STD_CONFIG_DIR))
+}
+
+/// Finds the closest directory, starting from `start` and walking up its
+/// parents, that contains a config dir.
+pub fn find_root(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| dir.join(STD_CONFIG_DIR).is_dir())
+        .map(Path::to_path_buf)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,4 +165,69 @@ mod tests {
         let link_dir = settings.link_dir;
         assert_eq!(link_dir, "test_dir/fileclass/temp/links");
     }
+
+    #[test]
+    fn load_uses_the_parent_dir_as_root() {
+        let config = Config::load("test_dir/fileclass").unwrap();
+
+        assert_eq!(config.root, Path::new("test_dir"));
+        assert!(config.conflicts.is_empty());
+    }
+
+    #[test]
+    fn load_reports_include_conflicts() {
+        let config = Config::load_layers(
+            PathBuf::from("test_dir"),
+            &[PathBuf::from("test_dir/includes")],
+        )
+        .unwrap();
+
+        assert_eq!(config.conflicts.len(), 2);
+
+        let warnings = config.warnings();
+        assert!(warnings[0].starts_with("Label \"cat\" is defined in"));
+        assert!(warnings[1].starts_with("\"pet\" refers to label \"cat\""));
+    }
+
+    #[test]
+    fn load_layers_works() {
+        let config = Config::load_layers(
+            PathBuf::from("test_dir"),
+            &[
+                PathBuf::from("test_dir/global_config"),
+                PathBuf::from("test_dir/fileclass"),
+            ],
+        )
+        .unwrap();
+
+        let labels = config.labels;
+
+        assert_eq!(labels.resolve("alias"), "label");
+        assert_eq!(labels.get_description("label"), "a label");
+        assert_eq!(labels.resolve("global_alias"), "global");
+        assert_eq!(config.settings.link_dir, "test_dir/fileclass/temp/links");
+    }
+
+    #[test]
+    fn load_layers_works_without_files() {
+        let config =
+            Config::load_layers(PathBuf::from("src"), &[PathBuf::from("src/fileclass")]).unwrap();
+
+        assert!(config.labels.label_names().is_empty());
+        assert_eq!(config.settings, Settings::default());
+        assert_eq!(config.root, Path::new("src"));
+    }
+
+    #[test]
+    fn find_root_works() {
+        assert_eq!(
+            find_root(Path::new("test_dir/files")),
+            Some(PathBuf::from("test_dir"))
+        );
+        assert_eq!(
+            find_root(Path::new("test_dir")),
+            Some(PathBuf::from("test_dir"))
+        );
+        assert_eq!(find_root(Path::new("src/core")), None);
+    }
 }
@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::label::LabelLibrary;
+
+/// Reserved key of a labels file listing other files or directories to include.
+pub const INCLUDE_KEY: &str = "include";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LabelConflict {
+    /// The same label is defined by more than one included file. The one
+    /// included last is used.
+    Redefined { name: String, sources: [PathBuf; 2] },
+    /// A name or alias refers to two different labels. Only one of them will
+    /// ever be resolved.
+    Ambiguous {
+        alias: String,
+        labels: [(String, PathBuf); 2],
+    },
+}
+
+impl fmt::Display for LabelConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LabelConflict::Redefined { name, sources } => write!(
+                f,
+                "Label \"{}\" is defined in \"{}\" and \"{}\", the latter is used",
+                name,
+                sources[0].display(),
+                sources[1].display()
+            ),
+            LabelConflict::Ambiguous { alias, labels } => write!(
+                f,
+                "\"{}\" refers to label \"{}\" (defined in \"{}\") and label \"{}\" (defined in \"{}\")",
+                alias,
+                labels[0].0,
+                labels[0].1.display(),
+                labels[1].0,
+                labels[1].1.display()
+            ),
+        }
+    }
+}
+
+struct Layer {
+    library: LabelLibrary,
+    /// Canonical path of the file defining each label.
+    sources: HashMap<String, PathBuf>,
+}
+
+/// Loads a labels file resolving its includes.
+///
+/// Override rules:
+/// - Includes are merged in the listed order. Directories are expanded to the
+///   `.toml` files they contain, sorted by name.
+/// - Labels defined by more than one included file are reported as conflicts
+///   and the last definition is used.
+/// - Labels defined in a file override the ones coming from its includes
+///   without being reported.
+///
+/// Include paths are relative to the file declaring them.
+pub fn load_labels(path: &Path) -> Result<(LabelLibrary, Vec<LabelConflict>), Box<dyn Error>> {
+    let mut conflicts = Vec::new();
+    let layer = load_layer(path, &mut Vec::new(), &mut conflicts)?;
+
+    conflicts.extend(find_ambiguities(&layer));
+
+    Ok((layer.library, conflicts))
+}
+
+fn load_layer(
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+    conflicts: &mut Vec<LabelConflict>,
+) -> Result<Layer, Box<dyn Error>> {
+    let canonical = fs::canonicalize(path)
+        .map_err(|err| format!("Can't read \"{}\": {}", path.display(), err))?;
+
+    if stack.contains(&canonical) {
+        return Err(format!("Include cycle detected at \"{}\"", path.display()).into());
+    }
+
+    let content = fs::read_to_string(&canonical)?;
+    let mut table: toml::Table =
+        toml::from_str(&content).map_err(|err| format!("{}: {}", path.display(), err))?;
+
+    let includes =
+        take_includes(&mut table).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+
+    stack.push(canonical.clone());
+
+    let mut library = LabelLibrary::empty();
+    let mut sources: HashMap<String, PathBuf> = HashMap::new();
+
+    for include in includes {
+        for file in include_files(&base_dir.join(include))? {
+            let child = load_layer(&file, stack, conflicts)?;
+
+            for (name, source) in child.sources {
+                if let Some(previous) = sources.get(&name) {
+                    if previous != &source {
+                        conflicts.push(LabelConflict::Redefined {
+                            name: name.clone(),
+                            sources: [previous.clone(), source.clone()],
+                        });
+                    }
+                }
+
+                sources.insert(name, source);
+            }
+
+            library = library.merge(child.library)?;
+        }
+    }
+
+    stack.pop();
+
+    let own =
+        LabelLibrary::from_table(table).map_err(|err| format!("{}: {}", path.display(), err))?;
+
+    for name in own.label_names() {
+        sources.insert(name.to_string(), canonical.clone());
+    }
+
+    library = library.merge(own)?;
+
+    Ok(Layer { library, sources })
+}
+
+fn take_includes(table: &mut toml::Table) -> Result<Vec<String>, Box<dyn Error>> {
+    let error = || format!("`{}` must be an array of paths", INCLUDE_KEY);
+
+    match table.remove(INCLUDE_KEY) {
+        None => Ok(Vec::new()),
+        Some(toml::Value::Array(items)) => items
+            .into_iter()
+            .map(|item| match item {
+                toml::Value::String(path) => Ok(path),
+                _ => Err(error().into()),
+            })
+            .collect(),
+        Some(_) => Err(error().into()),
+    }
+}
+
+fn include_files(path: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files: Vec<PathBuf> = fs::read_dir(path)
+        .map_err(|err| format!("Can't read \"{}\": {}", path.display(), err))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+
+    files.sort();
+
+    Ok(files)
+}
+
+fn find_ambiguities(layer: &Layer) -> Vec<LabelConflict> {
+    let library = &layer.library;
+    let mut conflicts = Vec::new();
+    let mut owners: HashMap<&str, &str> = HashMap::new();
+
+    let mut names = library.label_names();
+    names.sort();
+
+    for name in names {
+        let aliases = library.get_aliases(name).iter().map(|a| a.as_str());
+
+        for alias in std::iter::once(name).chain(aliases) {
+            match owners.get(alias) {
+                Some(owner) if *owner != name => {
+                    conflicts.push(LabelConflict::Ambiguous {
+                        alias: alias.to_string(),
+                        labels: [
+                            (owner.to_string(), layer.sources[*owner].clone()),
+                            (name.to_string(), layer.sources[name].clone()),
+                        ],
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    owners.insert(alias, name);
+                }
+            }
+        }
+    }
+
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical(path: &str) -> PathBuf {
+        fs::canonicalize(path).unwrap()
+    }
+
+    #[test]
+    fn load_labels_works() {
+        let (library, conflicts) = load_labels(Path::new("test_dir/includes/labels.toml")).unwrap();
+
+        let mut names = library.label_names();
+        names.sort();
+
+        assert_eq!(names, vec!["cat", "color", "dog", "red"]);
+        assert_eq!(
+            library.get_description("dog"),
+            "Overridden by the root file"
+        );
+        assert_eq!(library.get_description("cat"), "Cat from animals.toml");
+        assert_eq!(library.resolve("crimson"), "red");
+
+        assert_eq!(
+            conflicts,
+            vec![
+                LabelConflict::Redefined {
+                    name: "cat".to_string(),
+                    sources: [
+                        canonical("test_dir/includes/base.toml"),
+                        canonical("test_dir/includes/shared/animals.toml"),
+                    ],
+                },
+                LabelConflict::Ambiguous {
+                    alias: "pet".to_string(),
+                    labels: [
+                        (
+                            "cat".to_string(),
+                            canonical("test_dir/includes/shared/animals.toml")
+                        ),
+                        (
+                            "dog".to_string(),
+                            canonical("test_dir/includes/labels.toml")
+                        ),
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn load_labels_detects_cycles() {
+        let error = match load_labels(Path::new("test_dir/includes/cycle.toml")) {
+            Ok(_) => panic!("Cycle not detected"),
+            Err(error) => error,
+        };
+
+        assert!(error.to_string().contains("cycle"));
+    }
+}
@@ -0,0 +1,312 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::label::normalize::{Normalization, UnicodeForm};
+
+/// Version of the settings schema written by this build.
+///
+/// Files without a `version` key are considered version `0`.
+pub const SETTINGS_VERSION: i64 = 1;
+pub const VERSION_KEY: &str = "version";
+pub const DEFAULT_LINK_DIR: &str = "fileclass/temp/links";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    pub version: i64,
+    pub link_dir: String,
+    pub label_case_folding: bool,
+    pub label_unicode_form: UnicodeForm,
+    pub label_strip_diacritics: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            link_dir: DEFAULT_LINK_DIR.to_string(),
+            label_case_folding: false,
+            label_unicode_form: UnicodeForm::None,
+            label_strip_diacritics: false,
+        }
+    }
+}
+
+impl Settings {
+    pub fn normalization(&self) -> Normalization {
+        Normalization {
+            case_folding: self.label_case_folding,
+            form: self.label_unicode_form,
+            strip_diacritics: self.label_strip_diacritics,
+        }
+    }
+
+    /// Default settings as a TOML table, also used to know the valid keys and
+    /// their types.
+    fn default_table() -> toml::Table {
+        match toml::Value::try_from(Settings::default()) {
+            Ok(toml::Value::Table(table)) => table,
+            _ => unreachable!("Settings always serialize into a table"),
+        }
+    }
+
+    /// Value of every setting, sorted by key, with its source.
+    pub fn entries(&self, sources: &SettingSources) -> Vec<(String, toml::Value, SettingSource)> {
+        let table = match toml::Value::try_from(self) {
+            Ok(toml::Value::Table(table)) => table,
+            _ => unreachable!("Settings always serialize into a table"),
+        };
+
+        let mut entries: Vec<_> = table
+            .into_iter()
+            .map(|(key, value)| {
+                let source = sources.get(&key);
+                (key, value, source)
+            })
+            .collect();
+
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingSource {
+    Default,
+    File(PathBuf),
+}
+
+impl fmt::Display for SettingSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingSource::Default => write!(f, "default"),
+            SettingSource::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Settings combined from several files, where later files override the keys
+/// of the previous ones.
+#[derive(Debug, Clone, Default)]
+pub struct SettingsLayers {
+    table: toml::Table,
+    sources: HashMap<String, PathBuf>,
+    warnings: Vec<String>,
+}
+
+impl SettingsLayers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_file(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+        let table =
+            toml::from_str(&content).map_err(|err| format!("{}: {}", path.display(), err))?;
+
+        self.add_table(table, path)
+            .map_err(|err| format!("{}: {}", path.display(), err).into())
+    }
+
+    pub fn add_table(
+        &mut self,
+        mut table: toml::Table,
+        source: &Path,
+    ) -> Result<(), Box<dyn Error>> {
+        migrate(&mut table)?;
+
+        let known = Settings::default_table();
+
+        for (key, value) in table {
+            if key == VERSION_KEY {
+                continue;
+            }
+
+            if !known.contains_key(&key) {
+                self.warnings.push(format!(
+                    "Unknown setting \"{}\" in \"{}\"",
+                    key,
+                    source.display()
+                ));
+                continue;
+            }
+
+            self.sources.insert(key.clone(), source.to_path_buf());
+            self.table.insert(key, value);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the combined settings, the source of each value and the
+    /// problems found that don't prevent using them.
+    pub fn build(self) -> Result<(Settings, SettingSources, Vec<String>), Box<dyn Error>> {
+        let settings: Settings = toml::Value::Table(self.table).try_into()?;
+        Ok((settings, SettingSources(self.sources), self.warnings))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SettingSources(HashMap<String, PathBuf>);
+
+impl SettingSources {
+    pub fn get(&self, key: &str) -> SettingSource {
+        match self.0.get(key) {
+            Some(path) => SettingSource::File(path.clone()),
+            None => SettingSource::Default,
+        }
+    }
+}
+
+/// Brings a settings table written with an older schema to the current one.
+fn migrate(table: &mut toml::Table) -> Result<(), Box<dyn Error>> {
+    let version = match table.get(VERSION_KEY) {
+        None => 0,
+        Some(toml::Value::Integer(version)) => *version,
+        Some(_) => return Err(format!("\"{}\" must be an integer", VERSION_KEY).into()),
+    };
+
+    if version > SETTINGS_VERSION {
+        return Err(format!(
+            "Settings version {} is newer than the supported version {}",
+            version, SETTINGS_VERSION
+        )
+        .into());
+    }
+
+    // Version 0 is the unversioned schema, which has the same keys as version 1.
+
+    table.insert(VERSION_KEY.to_string(), SETTINGS_VERSION.into());
+
+    Ok(())
+}
+
+/// Parses a raw value given by the user into the type expected by `key`.
+fn parse_value(key: &str, raw: &str) -> Result<toml::Value, Box<dyn Error>> {
+    let default = Settings::default_table()
+        .remove(key)
+        .ok_or_else(|| format!("Unknown setting \"{}\"", key))?;
+
+    let value = match default {
+        toml::Value::String(_) => toml::Value::String(raw.to_string()),
+        toml::Value::Integer(_) => toml::Value::Integer(raw.parse()?),
+        toml::Value::Boolean(_) => toml::Value::Boolean(raw.parse()?),
+        _ => toml::from_str::<toml::Table>(&format!("value = {}", raw))?
+            .remove("value")
+            .ok_or("Invalid value")?,
+    };
+
+    Ok(value)
+}
+
+/// Sets a setting in the settings table of `content`, returning the updated
+/// content. Comments and formatting are not preserved.
+pub fn set_value(content: &str, key: &str, raw_value: &str) -> Result<String, Box<dyn Error>> {
+    if key == VERSION_KEY {
+        return Err(format!("\"{}\" can't be set manually", VERSION_KEY).into());
+    }
+
+    let mut table: toml::Table = toml::from_str(content)?;
+    migrate(&mut table)?;
+
+    table.insert(key.to_string(), parse_value(key, raw_value)?);
+
+    // Make sure the result is still valid before returning it.
+    let _: Settings = toml::Value::Table(table.clone()).try_into()?;
+
+    Ok(toml::to_string(&table)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(toml: &str) -> toml::Table {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn settings_layers_work() {
+        let mut layers = SettingsLayers::new();
+        layers
+            .add_table(table(r#"link_dir = "global""#), Path::new("global.toml"))
+            .unwrap();
+        layers
+            .add_table(
+                table(
+                    r#"
+                    version = 1
+                    link_dir = "local"
+                    unknown = true
+                "#,
+                ),
+                Path::new("local.toml"),
+            )
+            .unwrap();
+
+        let (settings, sources, warnings) = layers.build().unwrap();
+
+        assert_eq!(settings.link_dir, "local");
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(
+            sources.get("link_dir"),
+            SettingSource::File(PathBuf::from("local.toml"))
+        );
+        assert_eq!(sources.get("version"), SettingSource::Default);
+        assert_eq!(
+            warnings,
+            vec!["Unknown setting \"unknown\" in \"local.toml\"".to_string()]
+        );
+    }
+
+    #[test]
+    fn settings_layers_use_defaults() {
+        let (settings, _, warnings) = SettingsLayers::new().build().unwrap();
+        assert_eq!(settings, Settings::default());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn settings_layers_reject_newer_versions() {
+        let mut layers = SettingsLayers::new();
+        let result = layers.add_table(table("version = 1000"), Path::new("s.toml"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn entries_works() {
+        let settings = Settings::default();
+        let entries = settings.entries(&SettingSources::default());
+        let keys: Vec<_> = entries.into_iter().map(|e| e.0).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "label_case_folding",
+                "label_strip_diacritics",
+                "label_unicode_form",
+                "link_dir",
+                "version"
+            ]
+        );
+    }
+
+    #[test]
+    fn set_value_works() {
+        let content = set_value(r#"link_dir = "a""#, "link_dir", "b").unwrap();
+        let result = table(&content);
+
+        assert_eq!(result["link_dir"].as_str(), Some("b"));
+        assert_eq!(result["version"].as_integer(), Some(SETTINGS_VERSION));
+
+        let content = set_value("", "label_unicode_form", "nfkc").unwrap();
+        assert_eq!(table(&content)["label_unicode_form"].as_str(), Some("nfkc"));
+        assert!(set_value("", "label_unicode_form", "nfx").is_err());
+
+        assert!(set_value("", "unknown", "b").is_err());
+        assert!(set_value("", "version", "2").is_err());
+    }
+}
@@ -1,48 +1,177 @@
-use super::label::LabelSet;
-use std::path::Path;
+use super::label::{LabelLibrary, LabelSet};
+use metadata::{FileMetadata, MetadataCache};
+use serde::{Serialize, Serializer};
+use std::path::{Path, PathBuf};
 
-const FILENAME_LABELS_DELIMITER: &str = " fn ";
+pub mod metadata;
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) const FILENAME_LABELS_DELIMITER: &str = " fn ";
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
 pub struct Document {
-    // Provisory name matching the `from_filename` function.
-    pub path: String,
+    /// Exact path as given, which may not be valid UTF-8. Labels and the name
+    /// are parsed from a lossy conversion of it.
+    #[serde(serialize_with = "serialize_lossy")]
+    pub path: PathBuf,
     pub labels: LabelSet,
     pub name: String,
+    /// Labels added to `labels` by the rules of a library instead of being
+    /// written in the filename.
+    pub derived: LabelSet,
+    #[serde(skip)]
+    pub metadata: MetadataCache,
+}
+
+/// Serializes a path replacing invalid UTF-8, instead of failing.
+fn serialize_lossy<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&path.to_string_lossy())
+}
+
+/// Splits the filename into its labels and the name.
+fn parse_file_name(path: &Path) -> (LabelSet, String) {
+    let filename = match path.file_name() {
+        Some(filename) => filename.to_string_lossy(),
+        None => "".into(),
+    };
+
+    match filename.split_once(FILENAME_LABELS_DELIMITER) {
+        Some((labels, name)) => {
+            // Does not require trim.
+            let labels = labels.split_whitespace().map(|s| s.to_string()).collect();
+            (labels, name.trim().to_string())
+        }
+        None => (LabelSet::empty(), filename.to_string()),
+    }
 }
 
 impl Document {
     pub fn from_filename(path: &str) -> Self {
-        let path = Path::new(path);
-        let filename = match path.file_name() {
-            Some(filename) => filename.to_string_lossy(),
-            None => "".into(),
-        };
-
-        match filename.split_once(FILENAME_LABELS_DELIMITER) {
-            Some((labels, name)) => {
-                // Does not require trim.
-                let labels = labels.split_whitespace().map(|s| s.to_string()).collect();
-                Self {
-                    labels,
-                    // Shall I use lossy here?
-                    path: path.to_str().unwrap().to_string(),
-                    name: name.trim().to_string(),
-                }
+        Self::from_path(Path::new(path))
+    }
+
+    pub fn from_path(path: &Path) -> Self {
+        let (labels, name) = parse_file_name(path);
+
+        Self {
+            labels,
+            path: path.to_path_buf(),
+            name,
+            derived: LabelSet::empty(),
+            metadata: MetadataCache::default(),
+        }
+    }
+
+    /// Labels exactly as written in the filename, before normalizing them or
+    /// applying rules.
+    pub fn written_labels(&self) -> LabelSet {
+        parse_file_name(&self.path).0
+    }
+
+    /// Gets the document ready to be matched against the library, normalizing
+    /// its labels and applying the file rules.
+    pub fn prepare(&mut self, library: &LabelLibrary) {
+        self.normalize_labels(library);
+        self.apply_rules(library);
+    }
+
+    pub fn normalize_labels(&mut self, library: &LabelLibrary) {
+        self.labels = self
+            .labels
+            .iter()
+            .map(|label| library.normalize(label).into_owned())
+            .collect();
+    }
+
+    /// Adds the labels of the rules of the library matching the path, name or
+    /// extension of the file, marking them as derived. Labels already present
+    /// are left untouched.
+    pub fn apply_rules(&mut self, library: &LabelLibrary) {
+        let matching: Vec<&String> = library
+            .rules()
+            .iter()
+            .filter(|rule| rule.matches_file(self))
+            .flat_map(|rule| rule.implies.iter())
+            .collect();
+
+        for label in matching {
+            if self.labels.insert(label.clone()) {
+                self.derived.insert(label.clone());
             }
-            None => Self {
-                labels: LabelSet::empty(),
-                path: path.to_str().unwrap().to_string(),
-                name: filename.to_string(),
-            },
         }
     }
+
+    /// Labels written in the filename, leaving out the ones derived by file
+    /// rules.
+    pub fn explicit_labels(&self) -> impl Iterator<Item = &String> {
+        self.labels
+            .iter()
+            .filter(|l| !self.derived.inner_set().contains(*l))
+    }
+
+    /// Checks the extension of the name, case insensitively and with or without
+    /// the leading dot. An empty extension matches names without one.
+    pub fn has_extension(&self, ext: &str) -> bool {
+        let ext = ext.strip_prefix('.').unwrap_or(ext);
+
+        match Path::new(&self.name).extension() {
+            Some(name_ext) => name_ext.to_string_lossy().eq_ignore_ascii_case(ext),
+            None => ext.is_empty(),
+        }
+    }
+
+    /// File metadata of the document, read from the file system the first
+    /// time it's needed. The path is resolved from the current directory.
+    pub fn metadata(&self) -> Option<&FileMetadata> {
+        self.metadata.get_or_read(&self.path)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn apply_rules_works() {
+        let library = LabelLibrary::from_toml(
+            r#"
+            [[rules]]
+            path = "photos/**"
+            implies = ["photo", "cat"]
+
+            [[rules]]
+            when = "photo"
+            implies = ["image"]
+        "#,
+        )
+        .unwrap();
+
+        let mut doc = Document::from_filename("photos/cat fn a.jpg");
+        doc.apply_rules(&library);
+
+        assert_eq!(doc.labels, LabelSet::from(["cat", "photo"]));
+        assert_eq!(doc.derived, LabelSet::from(["photo"]));
+        assert_eq!(doc.explicit_labels().collect::<Vec<_>>(), vec!["cat"]);
+
+        let mut doc = Document::from_filename("other/cat fn a.jpg");
+        doc.apply_rules(&library);
+
+        assert!(doc.derived.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn from_path_works_with_invalid_utf8() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = Path::new(OsStr::from_bytes(b"dir/l\xff l2 fn name\xfe.ext"));
+        let doc = Document::from_path(path);
+
+        assert_eq!(doc.path, path);
+        assert_eq!(doc.name, "name\u{FFFD}.ext");
+        assert_eq!(doc.labels, LabelSet::from(["l\u{FFFD}", "l2"]));
+    }
+
     #[test]
     fn from_filename_works() {
         let doc = Document::from_filename("path/to/   l1   l2  fn   name.ext  ");
@@ -73,6 +202,15 @@ mod tests {
         assert_eq!(doc.labels, LabelSet::from(["l1", "l2"]));
     }
 
+    #[test]
+    fn metadata_works() {
+        let doc = Document::from_filename("test_dir/files/b.ext.txt");
+        assert_eq!(doc.metadata().unwrap().size, 0);
+
+        let doc = Document::from_filename("test_dir/files/missing");
+        assert!(doc.metadata().is_none());
+    }
+
     #[test]
     fn from_filename_works_with_empty_name_and_empty_labels() {
         let doc = Document::from_filename("path/to/   fn   ");
@@ -0,0 +1,99 @@
+use std::cell::OnceCell;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    File,
+    Dir,
+    Symlink,
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMetadata {
+    pub kind: FileKind,
+    /// Size in bytes. For symlinks, the size of the target if it exists.
+    pub size: u64,
+    /// For symlinks, the modification time of the target if it exists.
+    pub modified: Option<SystemTime>,
+}
+
+impl FileMetadata {
+    /// Reads the metadata of `path` without following the last symlink for
+    /// the kind, but following it for the rest of the fields.
+    pub fn read(path: &Path) -> Option<Self> {
+        let link_metadata = fs::symlink_metadata(path).ok()?;
+        let file_type = link_metadata.file_type();
+
+        let kind = if file_type.is_symlink() {
+            FileKind::Symlink
+        } else if file_type.is_dir() {
+            FileKind::Dir
+        } else if file_type.is_file() {
+            FileKind::File
+        } else {
+            FileKind::Other
+        };
+
+        let metadata = match kind {
+            FileKind::Symlink => fs::metadata(path).unwrap_or(link_metadata),
+            _ => link_metadata,
+        };
+
+        Some(Self {
+            kind,
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// Lazily read file metadata of a document.
+///
+/// It's only a cache, so it's ignored when comparing documents.
+#[derive(Clone, Default)]
+pub struct MetadataCache(OnceCell<Option<FileMetadata>>);
+
+impl MetadataCache {
+    pub fn get_or_read(&self, path: &Path) -> Option<&FileMetadata> {
+        self.0.get_or_init(|| FileMetadata::read(path)).as_ref()
+    }
+}
+
+impl PartialEq for MetadataCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for MetadataCache {}
+
+impl fmt::Debug for MetadataCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.get() {
+            Some(metadata) => write!(f, "MetadataCache({:?})", metadata),
+            None => write!(f, "MetadataCache(<unread>)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_works() {
+        let metadata = FileMetadata::read(Path::new("test_dir/files/a.txt")).unwrap();
+        assert_eq!(metadata.kind, FileKind::File);
+        assert_eq!(metadata.size, 0);
+        assert!(metadata.modified.is_some());
+
+        let metadata = FileMetadata::read(Path::new("test_dir/files")).unwrap();
+        assert_eq!(metadata.kind, FileKind::Dir);
+
+        assert_eq!(FileMetadata::read(Path::new("test_dir/missing")), None);
+    }
+}
@@ -1,11 +1,22 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::error::Error;
 use std::iter::FromIterator;
 use std::iter::IntoIterator;
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+use normalize::Normalization;
+use rule::{take_rules, LabelRule};
+
+use crate::utils::glob::{glob_match, is_glob, unescape};
+
+pub mod normalize;
+pub mod rule;
+
+/// Serialized as a sorted list of labels.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+#[serde(into = "Vec<String>")]
 pub struct LabelSet(HashSet<String>);
 
 impl LabelSet {
@@ -21,18 +32,49 @@ impl LabelSet {
         &self.0
     }
 
-    pub fn expand_with(&mut self, library: &LabelLibrary) -> () {
+    /// Adds the aliases and implied labels of every label, including the
+    /// ones implied by label rules. File rules are applied by
+    /// `Document::apply_rules` instead.
+    ///
+    /// Rules are checked in order until none of the remaining ones matches, so
+    /// they can depend on labels implied by other rules. Each rule applies at
+    /// most once and labels are never removed.
+    pub fn expand_with(&mut self, library: &LabelLibrary) {
         let input_labels = &self.0;
         let mut output_labels = HashSet::new();
 
         for l in input_labels {
-            library.expand_into(&mut output_labels, &l);
+            library.expand_into(&mut output_labels, l);
         }
 
         self.0 = output_labels;
+
+        let mut pending: Vec<&LabelRule> =
+            library.rules.iter().filter(|r| r.when.is_some()).collect();
+
+        while let Some(index) = pending.iter().position(|rule| rule.matches_labels(self)) {
+            for implied in pending.remove(index).implies.iter() {
+                library.expand_into(&mut self.0, implied);
+            }
+        }
+    }
+
+    /// Checks if any of the labels matches `pattern`, which may be a glob like
+    /// `proj_*`. Special characters can be escaped with `\`.
+    pub fn contains_pattern(&self, pattern: &str) -> bool {
+        if is_glob(pattern) {
+            return self.0.iter().any(|l| glob_match(pattern, l));
+        }
+
+        self.0.contains(unescape(pattern).as_ref())
+    }
+
+    /// Adds a label, returning `false` if it was already present.
+    pub fn insert(&mut self, label: String) -> bool {
+        self.0.insert(label)
     }
 
-    pub fn iter(&self) -> std::collections::hash_set::Iter<String> {
+    pub fn iter(&self) -> std::collections::hash_set::Iter<'_, String> {
         self.0.iter()
     }
 }
@@ -70,6 +112,14 @@ impl<'a> FromIterator<&'a str> for LabelSet {
     }
 }
 
+impl From<LabelSet> for Vec<String> {
+    fn from(set: LabelSet) -> Self {
+        let mut labels: Vec<String> = set.into_iter().collect();
+        labels.sort();
+        labels
+    }
+}
+
 impl<const N: usize> From<[&str; N]> for LabelSet {
     fn from(array: [&str; N]) -> Self {
         Self::from_iter(array)
@@ -80,6 +130,8 @@ struct LabelDef {
     name: String,
     aliases: Vec<String>,
     implies: Vec<String>,
+    parent: Option<String>,
+    conflicts_with: Vec<String>,
     description: String,
 }
 
@@ -90,27 +142,100 @@ struct RawLabelDef {
     #[serde(default)]
     implies: Vec<String>,
     #[serde(default)]
+    parent: Option<String>,
+    #[serde(default, alias = "excludes")]
+    conflicts_with: Vec<String>,
+    #[serde(default)]
     description: String,
 }
 
 pub struct LabelLibrary {
     label_defs: Vec<LabelDef>,
+    rules: Vec<LabelRule>,
+    normalization: Normalization,
+    /// Resolved names of the labels conflicting with each label, in both
+    /// directions.
+    conflicts: HashMap<String, HashSet<String>>,
 }
 
 impl LabelLibrary {
-    fn build(defs: Vec<LabelDef>) -> Result<Self, Box<dyn Error>> {
-        Self::validate(&defs)?;
-        Ok(Self { label_defs: defs })
+    fn build(defs: Vec<LabelDef>, rules: Vec<LabelRule>, normalization: Normalization) -> Self {
+        let mut library = Self {
+            label_defs: defs,
+            rules,
+            normalization,
+            conflicts: HashMap::new(),
+        };
+
+        for def in library.label_defs.iter() {
+            for other in def.conflicts_with.iter() {
+                let other = library.resolve(other).to_string();
+
+                library
+                    .conflicts
+                    .entry(def.name.clone())
+                    .or_default()
+                    .insert(other.clone());
+                library
+                    .conflicts
+                    .entry(other)
+                    .or_default()
+                    .insert(def.name.clone());
+            }
+        }
+
+        library
     }
 
     pub fn empty() -> Self {
-        Self::build(Vec::new()).unwrap()
+        Self::build(Vec::new(), Vec::new(), Normalization::default())
     }
 
-    /// Validates that the label definitions are valid.
+    /// Validates that the label definitions are valid. Rules are validated
+    /// when read instead.
     ///
-    /// This is a placeholder for now.
-    fn validate(_defs: &Vec<LabelDef>) -> Result<(), Box<dyn Error>> {
+    /// Parents and conflicting labels that aren't defined are allowed, like
+    /// implied labels, since they may be defined in a file merged later.
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        for def in self.label_defs.iter() {
+            let mut visited = vec![def.name.as_str()];
+            let mut current = def;
+
+            while let Some(parent) = current.parent.as_deref() {
+                let Some(parent) = self.get_label_def(parent) else {
+                    break;
+                };
+
+                if visited.contains(&parent.name.as_str()) {
+                    return Err(format!("Parent cycle detected at label \"{}\"", def.name).into());
+                }
+
+                visited.push(&parent.name);
+                current = parent;
+            }
+        }
+
+        for def in self.label_defs.iter() {
+            if def
+                .conflicts_with
+                .iter()
+                .any(|c| self.resolve(c) == def.name)
+            {
+                return Err(format!("Label \"{}\" conflicts with itself", def.name).into());
+            }
+
+            let mut labels = LabelSet::from([def.name.as_str()]);
+            labels.expand_with(self);
+
+            if let Some([a, b]) = self.find_conflicts(&labels).into_iter().next() {
+                return Err(format!(
+                    "Label \"{}\" implies both \"{}\" and \"{}\", which conflict",
+                    def.name, a, b
+                )
+                .into());
+            }
+        }
+
         Ok(())
     }
 
@@ -119,20 +244,76 @@ impl LabelLibrary {
     }
 
     fn get_label_def(&self, name: &str) -> Option<&LabelDef> {
+        let name = self.normalize(name);
+
         let def = self
             .label_defs
             .iter()
-            .find(|l| l.name == name || l.aliases.contains(&name.to_string()))?;
+            .find(|l| l.name == name || l.aliases.iter().any(|a| *a == name))?;
 
         Some(def)
     }
 
+    /// Normalizes a label from a document or a query so it can be compared
+    /// with the labels of the library.
+    pub fn normalize<'a>(&self, label: &'a str) -> Cow<'a, str> {
+        self.normalization.apply(label)
+    }
+
+    /// Returns the library with every label normalized, to be done after
+    /// merging all of them. The definitions are validated here, once.
+    pub fn normalized(self, normalization: Normalization) -> Result<Self, Box<dyn Error>> {
+        let apply = |label: String| normalization.apply(&label).into_owned();
+        let apply_all = |labels: Vec<String>| labels.into_iter().map(apply).collect();
+
+        let defs: Vec<LabelDef> = self
+            .label_defs
+            .into_iter()
+            .map(|def| LabelDef {
+                name: apply(def.name),
+                aliases: apply_all(def.aliases),
+                implies: apply_all(def.implies),
+                parent: def.parent.map(apply),
+                conflicts_with: apply_all(def.conflicts_with),
+                description: def.description,
+            })
+            .collect();
+
+        let mut names: Vec<&str> = defs.iter().map(|d| d.name.as_str()).collect();
+        names.sort();
+
+        if let Some(pair) = names.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(format!(
+                "More than one label is normalized into \"{}\", merge them or use aliases",
+                pair[0]
+            )
+            .into());
+        }
+
+        let rules = self
+            .rules
+            .into_iter()
+            .map(|rule| LabelRule {
+                // Syntax of queries is not affected by normalizing. Conditions
+                // on the file are matched as they are.
+                when: rule.when.map(apply),
+                implies: apply_all(rule.implies),
+                ..rule
+            })
+            .collect();
+
+        let library = Self::build(defs, rules, normalization);
+        library.validate()?;
+
+        Ok(library)
+    }
+
     pub fn resolve_known(&self, name: &str) -> Option<&str> {
         let def = self.get_label_def(name)?;
         Some(&def.name)
     }
 
-    pub fn resolve<'a>(&'a self, name: &'a str) -> &str {
+    pub fn resolve<'a>(&'a self, name: &'a str) -> &'a str {
         match self.resolve_known(name) {
             Some(known_name) => known_name,
             None => name,
@@ -145,7 +326,7 @@ impl LabelLibrary {
 
     // TODO: Consider moving into LabelSet using `get_label_def` from there.
     // Also consider adjusting the logic since some of this may no longer be necessary.
-    fn expand_into(&self, labels: &mut HashSet<String>, name: &str) -> () {
+    fn expand_into(&self, labels: &mut HashSet<String>, name: &str) {
         match self.get_label_def(name) {
             Some(def) => {
                 labels.insert(def.name.clone());
@@ -154,7 +335,8 @@ impl LabelLibrary {
                     labels.insert(alias.clone());
                 }
 
-                for implied in def.implies.iter() {
+                // A label implies its parent.
+                for implied in def.implies.iter().chain(def.parent.iter()) {
                     if !labels.contains(implied.as_str()) {
                         self.expand_into(labels, implied);
                     }
@@ -175,30 +357,200 @@ impl LabelLibrary {
 
     pub fn get_aliases(&self, name: &str) -> &[String] {
         match self.get_label_def(name) {
-            Some(def) => &def.aliases.as_slice(),
+            Some(def) => def.aliases.as_slice(),
             None => &[],
         }
     }
 
+    /// Labels implied by the label, as written in its definition.
+    pub fn get_implies(&self, name: &str) -> &[String] {
+        match self.get_label_def(name) {
+            Some(def) => def.implies.as_slice(),
+            None => &[],
+        }
+    }
+
+    /// Names of the labels directly implying the given one, sorted.
+    pub fn implied_by(&self, name: &str) -> Vec<&str> {
+        let name = self.resolve(name);
+
+        let mut labels: Vec<&str> = self
+            .label_defs
+            .iter()
+            .filter(|def| def.implies.iter().any(|l| self.resolve(l) == name))
+            .map(|def| def.name.as_str())
+            .collect();
+
+        labels.sort();
+        labels
+    }
+
+    /// Name of the parent of the label, as written in its definition.
+    pub fn get_parent(&self, name: &str) -> Option<&str> {
+        self.get_label_def(name)?.parent.as_deref()
+    }
+
+    /// Resolved names of the ancestors of the label, nearest first. Stops at
+    /// the first parent that isn't defined, which is included.
+    pub fn ancestors(&self, name: &str) -> Vec<&str> {
+        let mut ancestors = Vec::new();
+        let mut current = self.get_label_def(name);
+
+        while let Some(parent) = current.and_then(|def| def.parent.as_deref()) {
+            current = self.get_label_def(parent);
+            ancestors.push(current.map_or(parent, |def| def.name.as_str()));
+        }
+
+        ancestors
+    }
+
+    /// Names of the labels whose parent is the given label, sorted.
+    pub fn children(&self, name: &str) -> Vec<&str> {
+        let name = self.resolve(name);
+
+        let mut children: Vec<&str> = self
+            .label_defs
+            .iter()
+            .filter(|def| {
+                def.parent
+                    .as_deref()
+                    .is_some_and(|parent| self.resolve(parent) == name)
+            })
+            .map(|def| def.name.as_str())
+            .collect();
+
+        children.sort();
+        children
+    }
+
+    /// Names of every label below the given one in the hierarchy, depth first.
+    pub fn descendants(&self, name: &str) -> Vec<&str> {
+        let mut descendants = Vec::new();
+
+        for child in self.children(name) {
+            descendants.push(child);
+            descendants.extend(self.descendants(child));
+        }
+
+        descendants
+    }
+
+    /// Names of the labels without a defined parent, sorted.
+    pub fn roots(&self) -> Vec<&str> {
+        let mut roots: Vec<&str> = self
+            .label_defs
+            .iter()
+            .filter(|def| def.parent.as_deref().is_none_or(|p| !self.is_known(p)))
+            .map(|def| def.name.as_str())
+            .collect();
+
+        roots.sort();
+        roots
+    }
+
+    /// Resolved names of the labels conflicting with the given one, in both
+    /// directions, sorted. Works with labels that aren't defined too.
+    pub fn conflicts(&self, name: &str) -> Vec<&str> {
+        let mut conflicts: Vec<&str> = self
+            .conflicts
+            .get(self.resolve(name))
+            .into_iter()
+            .flatten()
+            .map(|c| c.as_str())
+            .collect();
+
+        conflicts.sort();
+        conflicts
+    }
+
+    /// Pairs of conflicting labels present in an already expanded set, each
+    /// pair sorted and reported once.
+    pub fn find_conflicts(&self, labels: &LabelSet) -> Vec<[String; 2]> {
+        let mut names: Vec<&str> = labels.iter().map(|l| self.resolve(l)).collect();
+
+        names.sort();
+        names.dedup();
+
+        let mut conflicts = Vec::new();
+
+        for (i, name) in names.iter().enumerate() {
+            let Some(name_conflicts) = self.conflicts.get(*name) else {
+                continue;
+            };
+
+            for other in names[i + 1..].iter() {
+                if name_conflicts.contains(*other) {
+                    conflicts.push([name.to_string(), other.to_string()]);
+                }
+            }
+        }
+
+        conflicts
+    }
+
+    pub fn rules(&self) -> &[LabelRule] {
+        &self.rules
+    }
+
+    /// Combines two libraries. Definitions in `overrides` replace the ones with
+    /// the same name in `self`. Rules of both are kept, the ones of `self`
+    /// first.
+    ///
+    /// The result isn't validated, since more libraries may be merged into it.
+    /// `normalized` validates it once every library is merged.
+    pub fn merge(self, overrides: LabelLibrary) -> Result<Self, Box<dyn Error>> {
+        let mut defs: Vec<LabelDef> = self
+            .label_defs
+            .into_iter()
+            .filter(|d| !overrides.label_defs.iter().any(|o| o.name == d.name))
+            .collect();
+
+        defs.extend(overrides.label_defs);
+
+        let mut rules = self.rules;
+        rules.extend(overrides.rules);
+
+        Ok(Self::build(defs, rules, self.normalization))
+    }
+
     pub fn from_toml(toml: &str) -> Result<Self, Box<dyn Error>> {
-        let raw_labels: HashMap<String, RawLabelDef> = toml::from_str(toml)?;
+        let library = Self::from_table(toml::from_str(toml)?)?;
+        library.validate()?;
+
+        Ok(library)
+    }
+
+    /// Same as `from_toml` but from an already parsed TOML table. Only the
+    /// rules are validated, like with `merge`.
+    pub fn from_table(mut table: toml::Table) -> Result<Self, Box<dyn Error>> {
+        let rules = take_rules(&mut table)?;
+
+        for rule in rules.iter() {
+            rule.validate()?;
+        }
+
+        let raw_labels: HashMap<String, RawLabelDef> = toml::Value::Table(table).try_into()?;
         let labels = raw_labels
             .into_iter()
             .map(|(name, raw)| LabelDef {
                 name,
                 aliases: raw.aliases,
                 implies: raw.implies,
+                parent: raw.parent,
+                conflicts_with: raw.conflicts_with,
                 description: raw.description,
             })
             .collect();
 
-        Self::build(labels)
+        Ok(Self::build(labels, rules, Normalization::default()))
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 pub mod tests {
     use super::*;
+    use crate::core::document::Document;
 
     pub fn setup_library() -> LabelLibrary {
         let labels = vec![
@@ -206,53 +558,69 @@ pub mod tests {
                 name: "cute".to_string(),
                 aliases: vec!["adorable".to_string(), "kawaii".to_string()],
                 implies: vec![],
+                parent: None,
+                conflicts_with: vec![],
                 description: "Something cute".to_string(),
             },
             LabelDef {
                 name: "pet".to_string(),
                 aliases: vec![],
                 implies: vec!["cute".to_string()],
+                parent: None,
+                conflicts_with: vec![],
                 description: "Pet related".to_string(),
             },
             LabelDef {
                 name: "cat".to_string(),
                 aliases: vec!["kitty".to_string(), "purrr".to_string()],
                 implies: vec!["pet".to_string()],
+                parent: None,
+                conflicts_with: vec![],
                 description: "Cat related".to_string(),
             },
             LabelDef {
                 name: "tiger".to_string(),
                 aliases: vec![],
                 implies: vec!["cat".to_string()],
+                parent: None,
+                conflicts_with: vec![],
                 description: "Tiger stuff".to_string(),
             },
             LabelDef {
                 name: "dog".to_string(),
                 aliases: vec!["puppy".to_string()],
                 implies: vec!["pet".to_string()],
+                parent: None,
+                conflicts_with: vec![],
                 description: "Dog related".to_string(),
             },
             LabelDef {
                 name: "meme".to_string(),
                 aliases: vec!["memes".to_string()],
                 implies: vec![],
+                parent: None,
+                conflicts_with: vec![],
                 description: "Something funny".to_string(),
             },
             LabelDef {
                 name: "rec_1".to_string(),
                 aliases: vec![],
                 implies: vec!["rec_2".to_string()],
+                parent: None,
+                conflicts_with: vec![],
                 description: "".to_string(),
             },
             LabelDef {
                 name: "rec_2".to_string(),
                 aliases: vec![],
                 implies: vec!["rec_1".to_string()],
+                parent: None,
+                conflicts_with: vec![],
                 description: "".to_string(),
             },
         ];
 
-        LabelLibrary::build(labels).unwrap()
+        LabelLibrary::build(labels, Vec::new(), Normalization::default())
     }
 
     #[test]
@@ -327,6 +695,14 @@ pub mod tests {
         assert_eq!(library.get_description("puppy"), "Dog related");
     }
 
+    #[test]
+    fn implied_by_works() {
+        let library = setup_library();
+        assert_eq!(library.implied_by("kitty"), vec!["tiger"]);
+        assert_eq!(library.implied_by("pet"), vec!["cat", "dog"]);
+        assert!(library.implied_by("tiger").is_empty());
+    }
+
     #[test]
     fn get_aliases_works() {
         let library = setup_library();
@@ -354,6 +730,328 @@ pub mod tests {
         assert_eq!(label_description, "a label");
     }
 
+    #[test]
+    fn merge_works() {
+        let base = LabelLibrary::from_toml(
+            r#"
+            [cat]
+            description = "base cat"
+            aliases = ["kitty"]
+
+            [dog]
+        "#,
+        )
+        .unwrap();
+
+        let overrides = LabelLibrary::from_toml(
+            r#"
+            [cat]
+            description = "local cat"
+
+            [bird]
+        "#,
+        )
+        .unwrap();
+
+        let library = base.merge(overrides).unwrap();
+
+        let mut names = library.label_names();
+        names.sort();
+
+        assert_eq!(names, vec!["bird", "cat", "dog"]);
+        assert_eq!(library.get_description("cat"), "local cat");
+        assert!(!library.is_known("kitty"));
+    }
+
+    fn setup_hierarchy() -> LabelLibrary {
+        LabelLibrary::from_toml(
+            r#"
+            [animal]
+
+            [mammal]
+            parent = "animal"
+
+            [cat]
+            aliases = ["kitty"]
+            parent = "mammal"
+
+            [dog]
+            parent = "mammal"
+
+            [bird]
+            parent = "animal"
+
+            [lost]
+            parent = "undefined"
+        "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn expand_with_includes_ancestors() {
+        let library = setup_hierarchy();
+
+        let mut result = LabelSet::from(["kitty"]);
+        result.expand_with(&library);
+
+        let expected = LabelSet::from(["animal", "cat", "kitty", "mammal"]);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn hierarchy_apis_work() {
+        let library = setup_hierarchy();
+
+        assert_eq!(library.get_parent("kitty"), Some("mammal"));
+        assert_eq!(library.ancestors("kitty"), vec!["mammal", "animal"]);
+        assert_eq!(library.ancestors("lost"), vec!["undefined"]);
+        assert_eq!(library.children("mammal"), vec!["cat", "dog"]);
+        assert_eq!(
+            library.descendants("animal"),
+            vec!["bird", "mammal", "cat", "dog"]
+        );
+        assert_eq!(library.roots(), vec!["animal", "lost"]);
+    }
+
+    #[test]
+    fn parent_cycles_are_rejected() {
+        let result = LabelLibrary::from_toml(
+            r#"
+            [a]
+            parent = "b"
+
+            [b]
+            aliases = ["c"]
+            parent = "c"
+        "#,
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn expand_with_applies_rules_until_fixpoint() {
+        let library = LabelLibrary::from_toml(
+            r#"
+            [[rules]]
+            when = "catmeme"
+            implies = ["funny"]
+
+            [[rules]]
+            when = "kitty meme not:dog"
+            implies = ["catmeme"]
+
+            [cat]
+            aliases = ["kitty"]
+
+            [catmeme]
+            implies = ["meme"]
+        "#,
+        )
+        .unwrap();
+
+        let mut result = LabelSet::from(["cat", "meme"]);
+        result.expand_with(&library);
+
+        let expected = LabelSet::from(["cat", "catmeme", "funny", "kitty", "meme"]);
+
+        assert_eq!(result, expected);
+
+        let mut result = LabelSet::from(["cat", "meme", "dog"]);
+        result.expand_with(&library);
+
+        let expected = LabelSet::from(["cat", "dog", "kitty", "meme"]);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn expand_with_applies_rules_in_written_order() {
+        // The `not:` rule comes first, so it's applied before `b` is implied.
+        let library = LabelLibrary::from_toml(
+            r#"
+            [[rules]]
+            when = "c not:b"
+            implies = ["a"]
+
+            [[rules]]
+            when = "c"
+            implies = ["b"]
+        "#,
+        )
+        .unwrap();
+
+        let mut result = LabelSet::from(["c"]);
+        result.expand_with(&library);
+
+        assert_eq!(result, LabelSet::from(["a", "b", "c"]));
+    }
+
+    #[test]
+    fn find_conflicts_works() {
+        let library = LabelLibrary::from_toml(
+            r#"
+            [draft]
+            conflicts_with = ["final"]
+
+            [final]
+            aliases = ["done"]
+
+            [published]
+            implies = ["done"]
+            excludes = ["private"]
+
+            [private]
+
+            [wip]
+            conflicts_with = ["archived"]
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(library.conflicts("done"), vec!["draft"]);
+        assert_eq!(library.conflicts("published"), vec!["private"]);
+
+        let mut labels = LabelSet::from(["draft", "published", "private"]);
+        labels.expand_with(&library);
+
+        assert_eq!(
+            library.find_conflicts(&labels),
+            vec![
+                ["draft".to_string(), "final".to_string()],
+                ["private".to_string(), "published".to_string()],
+            ]
+        );
+
+        let mut labels = LabelSet::from(["draft", "other"]);
+        labels.expand_with(&library);
+
+        assert!(library.find_conflicts(&labels).is_empty());
+
+        // Targets don't need to be defined.
+        assert_eq!(library.conflicts("archived"), vec!["wip"]);
+
+        let mut labels = LabelSet::from(["archived", "wip"]);
+        labels.expand_with(&library);
+
+        assert_eq!(
+            library.find_conflicts(&labels),
+            vec![["archived".to_string(), "wip".to_string()]]
+        );
+    }
+
+    #[test]
+    fn merged_libraries_are_validated_when_normalized() {
+        let base = LabelLibrary::from_toml(
+            r#"
+            [a]
+            implies = ["b"]
+        "#,
+        )
+        .unwrap();
+
+        let overrides = LabelLibrary::from_toml(
+            r#"
+            [b]
+            conflicts_with = ["a"]
+        "#,
+        )
+        .unwrap();
+
+        let library = base.merge(overrides).unwrap();
+
+        assert_eq!(library.conflicts("a"), vec!["b"]);
+        assert!(library.normalized(Normalization::default()).is_err());
+    }
+
+    #[test]
+    fn conflicting_implications_are_rejected() {
+        let result = LabelLibrary::from_toml(
+            r#"
+            [a]
+            implies = ["b"]
+            conflicts_with = ["b"]
+
+            [b]
+        "#,
+        );
+
+        assert!(result.is_err());
+
+        let result = LabelLibrary::from_toml(
+            r#"
+            [a]
+            conflicts_with = ["a"]
+        "#,
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn normalized_works() {
+        let library = LabelLibrary::from_toml(
+            r#"
+            [Cat]
+            aliases = ["Kitty"]
+            implies = ["Pet"]
+
+            [pet]
+        "#,
+        )
+        .unwrap();
+
+        let normalization = Normalization {
+            case_folding: true,
+            ..Default::default()
+        };
+
+        let library = library.normalized(normalization).unwrap();
+
+        assert_eq!(library.resolve("KITTY"), "cat");
+        assert!(library.is_known("PET"));
+
+        let mut result = LabelSet::from(["kitty"]);
+        result.expand_with(&library);
+
+        assert_eq!(result, LabelSet::from(["cat", "kitty", "pet"]));
+
+        let rules = LabelLibrary::from_toml(
+            r#"
+            [[rules]]
+            when = "Cat not:Dog"
+            implies = ["Funny"]
+
+            [[rules]]
+            path = "Photos/**"
+            implies = ["Photo"]
+        "#,
+        )
+        .unwrap()
+        .normalized(normalization)
+        .unwrap();
+
+        let mut result = LabelSet::from(["cat"]);
+        result.expand_with(&rules);
+
+        assert_eq!(result, LabelSet::from(["cat", "funny"]));
+
+        let mut document = Document::from_filename("Photos/a.jpg");
+        document.prepare(&rules);
+
+        assert_eq!(document.derived, LabelSet::from(["photo"]));
+
+        let mut document = Document::from_filename("PHOTOS/a.jpg");
+        document.prepare(&rules);
+
+        assert!(document.derived.is_empty());
+
+        let duplicated = LabelLibrary::from_toml("[Cat]\n[cat]").unwrap();
+        assert!(duplicated.normalized(normalization).is_err());
+    }
+
     #[test]
     fn resolve_known_works() {
         let library = setup_library();
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Unicode normalization form applied to labels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnicodeForm {
+    #[default]
+    None,
+    /// Canonical composition, so `é` typed as one or two code points is the
+    /// same label.
+    Nfc,
+    /// Compatibility composition, which also unifies variants like `ﬁ` and
+    /// `fi` or full width characters.
+    Nfkc,
+}
+
+/// How labels are normalized before being compared, so the same label typed
+/// on different devices matches.
+///
+/// Applied to the label definitions, the labels of documents and the labels
+/// in queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Normalization {
+    /// Compares labels ignoring case.
+    pub case_folding: bool,
+    pub form: UnicodeForm,
+    /// Removes accents and other combining marks, so `café` matches `cafe`.
+    pub strip_diacritics: bool,
+}
+
+impl Normalization {
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn apply<'a>(&self, label: &'a str) -> Cow<'a, str> {
+        if self.is_identity() || (label.is_ascii() && !self.has_ascii_uppercase(label)) {
+            return Cow::Borrowed(label);
+        }
+
+        let mut label = if self.case_folding {
+            label.to_lowercase()
+        } else {
+            label.to_string()
+        };
+
+        if self.strip_diacritics {
+            label = label.nfd().filter(|c| !is_combining_mark(*c)).collect();
+        }
+
+        label = match self.form {
+            // Stripping decomposes, so compose again to keep other marks and
+            // characters intact as much as possible.
+            UnicodeForm::None if self.strip_diacritics => label.nfc().collect(),
+            UnicodeForm::None => label,
+            UnicodeForm::Nfc => label.nfc().collect(),
+            UnicodeForm::Nfkc => label.nfkc().collect(),
+        };
+
+        Cow::Owned(label)
+    }
+
+    /// ASCII labels only change when folding their case.
+    fn has_ascii_uppercase(&self, label: &str) -> bool {
+        self.case_folding && label.bytes().any(|b| b.is_ascii_uppercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_works() {
+        let none = Normalization::default();
+        let case = Normalization {
+            case_folding: true,
+            ..Default::default()
+        };
+        let nfkc = Normalization {
+            form: UnicodeForm::Nfkc,
+            ..Default::default()
+        };
+        let all = Normalization {
+            case_folding: true,
+            form: UnicodeForm::Nfc,
+            strip_diacritics: true,
+        };
+
+        assert_eq!(none.apply("Kitty"), "Kitty");
+        assert_eq!(case.apply("Kitty"), "kitty");
+        assert_eq!(case.apply("CAFÉ"), "café");
+        assert_eq!(nfkc.apply("ﬁsh"), "fish");
+        assert_eq!(nfkc.apply("cafe\u{301}"), "café");
+        assert_eq!(all.apply("KİTTY"), "kitty");
+        assert_eq!(all.apply("Café"), "cafe");
+        assert_eq!(all.apply("cafe\u{301}"), "cafe");
+    }
+}
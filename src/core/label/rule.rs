@@ -0,0 +1,332 @@
+use regex::Regex;
+use serde::Deserialize;
+use std::error::Error;
+
+use crate::core::document::Document;
+use crate::core::label::LabelSet;
+use crate::utils::glob::glob_match;
+use crate::utils::regex_cache;
+
+/// Reserved key of a labels file holding the list of rules.
+pub const RULES_KEY: &str = "rules";
+
+/// Negation, the only pseudo label allowed in `when`.
+const NOT_PREFIX: &str = "not:";
+
+/// Implies labels when a condition holds.
+///
+/// There are two kinds of rules:
+/// - Label rules, with a `when` query checked against the expanded labels of a
+///   document, like `cat meme` implying `catmeme`.
+/// - File rules, with any of `path`, `ext` and `name` checked against the file
+///   before expansion. All the given conditions must match.
+///
+/// Label rules are applied by picking the first rule, in the order they are
+/// written, that matches and hasn't been applied yet, until none is left. So a
+/// `not:` term only sees the labels implied by the rules applied before it.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LabelRule {
+    /// Query evaluated against the expanded labels of a document. Only labels,
+    /// patterns and `not:` are allowed, since nothing else of the file is
+    /// known at that point.
+    pub when: Option<String>,
+    /// Glob matched against the path relative to the root.
+    pub path: Option<String>,
+    /// Extension of the file, case insensitive.
+    pub ext: Option<String>,
+    /// Regex matched against the name without labels.
+    pub name: Option<String>,
+    pub implies: Vec<String>,
+}
+
+impl LabelRule {
+    /// File conditions as their keys and values.
+    fn file_conditions(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("path", &self.path),
+            ("ext", &self.ext),
+            ("name", &self.name),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, value.as_deref()?)))
+    }
+
+    pub fn is_file_rule(&self) -> bool {
+        self.file_conditions().next().is_some()
+    }
+
+    /// Checks the condition of a label rule. Always `false` for file rules.
+    ///
+    /// `when` is normalized along with the library, so it's compared as is.
+    pub fn matches_labels(&self, labels: &LabelSet) -> bool {
+        match &self.when {
+            Some(when) => when
+                .split_whitespace()
+                .all(|term| check_when_term(term, labels)),
+            None => false,
+        }
+    }
+
+    /// Checks the conditions of a file rule. Always `false` for label rules.
+    ///
+    /// The path, extension and name are matched as they are, label
+    /// normalization doesn't apply to them.
+    pub fn matches_file(&self, document: &Document) -> bool {
+        if !self.is_file_rule() {
+            return false;
+        }
+
+        self.file_conditions().all(|(key, value)| match key {
+            "path" => glob_match(value, &document.path.to_string_lossy()),
+            "ext" => document.has_extension(value),
+            _ => regex_cache::is_match(value, &document.name),
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        let description = self.to_string();
+
+        if self.implies.is_empty() {
+            return Err(format!("Rule {} doesn't imply any label", description).into());
+        }
+
+        match &self.when {
+            Some(_) if self.is_file_rule() => Err(format!(
+                "Rule {} can't combine `when` with `path`, `ext` or `name`",
+                description
+            )
+            .into()),
+            Some(when) if when.trim().is_empty() => {
+                Err(format!("Rule {} has an empty `when` query", description).into())
+            }
+            Some(when) => {
+                for term in when.split_whitespace() {
+                    let mut term = term;
+                    while let Some(negated) = term.strip_prefix(NOT_PREFIX) {
+                        term = negated;
+                    }
+
+                    if let Some((prefix, _)) = term.split_once(':') {
+                        return Err(format!(
+                            "Rule {} can't use pseudo label \"{}:\" in `when`, only labels and `not:`",
+                            description, prefix
+                        )
+                        .into());
+                    }
+                }
+
+                Ok(())
+            }
+            None if !self.is_file_rule() => Err(format!(
+                "Rule {} requires `when`, `path`, `ext` or `name`",
+                description
+            )
+            .into()),
+            None => match &self.name {
+                Some(name) => match Regex::new(name) {
+                    Ok(_) => Ok(()),
+                    Err(err) => {
+                        Err(format!("Rule {} has an invalid regex: {}", description, err).into())
+                    }
+                },
+                None => Ok(()),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for LabelRule {
+    /// Compact description of the rule for error messages, like
+    /// `{ path = "photos/**" }`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let conditions: Vec<String> = self
+            .when
+            .as_deref()
+            .map(|when| ("when", when))
+            .into_iter()
+            .chain(self.file_conditions())
+            .map(|(key, value)| format!("{} = {:?}", key, value))
+            .collect();
+
+        write!(f, "{{ {} }}", conditions.join(", "))
+    }
+}
+
+/// Checks a label or pattern against the labels, negated by any `not:` in
+/// front of it.
+fn check_when_term(term: &str, labels: &LabelSet) -> bool {
+    match term.strip_prefix(NOT_PREFIX) {
+        Some(negated) => !check_when_term(negated, labels),
+        None => labels.contains_pattern(term),
+    }
+}
+
+/// Takes the rules out of a labels file table.
+pub fn take_rules(table: &mut toml::Table) -> Result<Vec<LabelRule>, Box<dyn Error>> {
+    match table.remove(RULES_KEY) {
+        None => Ok(Vec::new()),
+        Some(value) => value
+            .try_into()
+            .map_err(|err| format!("Invalid `{}`: {}", RULES_KEY, err).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(toml: &str) -> LabelRule {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn take_rules_works() {
+        let mut table: toml::Table = toml::from_str(
+            r#"
+            [[rules]]
+            when = "cat meme"
+            implies = ["catmeme"]
+
+            [cat]
+        "#,
+        )
+        .unwrap();
+
+        let rules = take_rules(&mut table).unwrap();
+
+        assert_eq!(
+            rules,
+            vec![LabelRule {
+                when: Some("cat meme".to_string()),
+                implies: vec!["catmeme".to_string()],
+                ..Default::default()
+            }]
+        );
+        assert!(!table.contains_key(RULES_KEY));
+    }
+
+    #[test]
+    fn validate_works() {
+        let valid = [
+            r#"when = "cat not:dog"
+            implies = ["x"]"#,
+            r#"path = "photos/**"
+            ext = "jpg"
+            implies = ["x"]"#,
+        ];
+
+        let invalid = [
+            r#"when = " "
+            implies = ["x"]"#,
+            r#"when = "cat""#,
+            r#"when = "sytem:labeled"
+            implies = ["x"]"#,
+            r#"implies = ["x"]"#,
+            r#"when = "cat"
+            path = "a/**"
+            implies = ["x"]"#,
+            r#"name = "("
+            implies = ["x"]"#,
+        ];
+
+        for toml in valid {
+            assert!(rule(toml).validate().is_ok(), "{}", toml);
+        }
+
+        for toml in invalid {
+            assert!(rule(toml).validate().is_err(), "{}", toml);
+        }
+    }
+
+    #[test]
+    fn validate_rejects_terms_reading_the_file() {
+        let terms = [
+            "explicit:cat",
+            "explicit:count=0",
+            "name:*.pdf",
+            "name~:^IMG",
+            "ext:pdf",
+            "path:a/**",
+            "unknown:cat",
+            "system:has-unknown",
+            "system:all-known",
+            "system:count>1",
+            "size:>1M",
+            "modified:<2024-01-01",
+            "type:dir",
+            "sytem:labeled",
+        ];
+
+        for term in terms {
+            for when in [term.to_string(), format!("cat not:{}", term)] {
+                let rule = LabelRule {
+                    when: Some(when.clone()),
+                    implies: vec!["x".to_string()],
+                    ..Default::default()
+                };
+
+                assert!(rule.validate().is_err(), "{}", when);
+            }
+        }
+    }
+
+    #[test]
+    fn matches_labels_works() {
+        let labels = LabelSet::from(["cat", "proj_a"]);
+
+        let matches = |when: &str| {
+            let rule = LabelRule {
+                when: Some(when.to_string()),
+                implies: vec!["x".to_string()],
+                ..Default::default()
+            };
+            rule.matches_labels(&labels)
+        };
+
+        assert!(matches("cat proj_*"));
+        assert!(matches("cat not:dog"));
+        assert!(matches("not:not:cat"));
+        assert!(!matches("cat not:proj_*"));
+        assert!(!matches("dog"));
+    }
+
+    #[test]
+    fn matches_file_works() {
+        let document = Document::from_filename("photos/2023/cat fn IMG_01.JPG");
+
+        let photos = rule(
+            r#"path = "photos/**"
+            implies = ["photo"]"#,
+        );
+        let jpg_images = rule(
+            r#"ext = "jpg"
+            name = "^IMG_"
+            implies = ["image"]"#,
+        );
+        let pdfs = rule(
+            r#"ext = "pdf"
+            implies = ["document"]"#,
+        );
+        let labels_only = rule(
+            r#"when = "cat"
+            implies = ["x"]"#,
+        );
+
+        assert!(photos.matches_file(&document));
+        assert!(jpg_images.matches_file(&document));
+        assert!(!pdfs.matches_file(&document));
+        assert!(!labels_only.matches_file(&document));
+    }
+
+    #[test]
+    fn display_works() {
+        let rule = rule(
+            r#"path = "a/**"
+            name = "^b"
+            implies = ["x"]"#,
+        );
+
+        assert_eq!(rule.to_string(), r#"{ path = "a/**", name = "^b" }"#);
+    }
+}
@@ -3,6 +3,11 @@ use super::{
     label::{LabelLibrary, LabelSet},
 };
 
+pub mod explain;
+pub mod matchers;
+pub mod rank;
+pub mod validate;
+
 const PSEUDO_DELIMITER: &str = ":";
 
 pub struct CheckParams<'a> {
@@ -11,49 +16,59 @@ pub struct CheckParams<'a> {
     pub library: &'a LabelLibrary,
 }
 
-pub fn check(params: &CheckParams) -> bool {
-    let CheckParams {
-        prompt,
-        document,
-        library,
-    } = params;
-    let searching_for: Vec<&str> = prompt.split_whitespace().collect();
-
-    let mut matches = true;
-
-    for label in searching_for.iter() {
-        let mut extended_labels = document.labels.clone();
-        extended_labels.expand_with(library);
+/// Everything a matcher may look at to decide if a document matches.
+pub struct MatchContext<'a> {
+    pub document: &'a Document,
+    /// Labels of the document expanded with the library.
+    pub labels: &'a LabelSet,
+    pub library: &'a LabelLibrary,
+}
 
-        if !check_table(document, &extended_labels, label) {
-            matches = false;
-            break;
+impl MatchContext<'_> {
+    /// Checks a single term of a query, which may be a label or a pseudo label.
+    pub fn check_term(&self, term: &str) -> bool {
+        match term.split_once(PSEUDO_DELIMITER) {
+            Some((prefix, suffix)) => match matchers::find(prefix) {
+                Some(matcher) => matcher(self, suffix),
+                None => false,
+            },
+            None => self.has_label(self.labels, term),
         }
     }
 
-    matches
-}
-
-fn check_table(document: &Document, labels: &LabelSet, current_label: &str) -> bool {
-    match current_label.split_once(PSEUDO_DELIMITER) {
-        Some((prefix, suffix)) => check_pseudo(document, labels, prefix, suffix),
-        None => check_presence(labels, current_label),
+    /// Checks if any of the labels matches a label or pattern from the query,
+    /// after normalizing it like the labels.
+    pub fn has_label(&self, labels: &LabelSet, label: &str) -> bool {
+        labels.contains_pattern(&self.library.normalize(label))
     }
 }
 
-fn check_pseudo(document: &Document, labels: &LabelSet, prefix: &str, suffix: &str) -> bool {
-    // TODO: Refator each type of pseudo matcher into it's own matcher module.
-    match (prefix, suffix) {
-        ("system", "unlabeled") => labels.is_empty(),
-        ("system", "labeled") => !labels.is_empty(),
-        ("not", _) => !check_table(document, labels, suffix),
-        ("explicit", _) => check_presence(&document.labels, suffix),
-        _ => false,
-    }
-}
+pub fn check(params: &CheckParams) -> bool {
+    let CheckParams {
+        prompt,
+        document,
+        library,
+    } = params;
+
+    let mut extended_labels = document.labels.clone();
+    extended_labels.expand_with(library);
 
-fn check_presence(labels: &LabelSet, current_label: &str) -> bool {
-    labels.iter().any(|l| l == current_label)
+    let context = MatchContext {
+        document,
+        labels: &extended_labels,
+        library,
+    };
+
+    // Terms reading the file system are checked last, so only documents
+    // surviving the rest of the terms are stat()ed.
+    let (metadata_terms, terms): (Vec<&str>, Vec<&str>) = prompt
+        .split_whitespace()
+        .partition(|term| matchers::reads_metadata(term));
+
+    terms
+        .into_iter()
+        .chain(metadata_terms)
+        .all(|term| context.check_term(term))
 }
 
 #[cfg(test)]
@@ -78,6 +93,8 @@ mod tests {
             path: "".into(),
             name: "name".into(),
             labels: LabelSet::from(["l1", "l2", "label"]),
+            derived: LabelSet::empty(),
+            metadata: Default::default(),
         };
 
         let mut params = CheckParams {
@@ -137,4 +154,131 @@ mod tests {
         params.prompt = "explicit:implied";
         assert!(!check(&params));
     }
+
+    #[test]
+    fn check_works_with_patterns() {
+        let toml = r#"
+            [label]
+            aliases = ["alias"]
+            implies = ["implied"]
+            description = "a label"
+
+            [implied]
+        "#;
+
+        let library = LabelLibrary::from_toml(toml).unwrap();
+
+        let document = Document {
+            path: "".into(),
+            name: "name".into(),
+            labels: LabelSet::from(["l1", "l2", "label"]),
+            derived: LabelSet::empty(),
+            metadata: Default::default(),
+        };
+
+        let mut params = CheckParams {
+            prompt: "",
+            document: &document,
+            library: &library,
+        };
+
+        params.prompt = "l*";
+        assert!(check(&params));
+
+        params.prompt = "imp*";
+        assert!(check(&params));
+
+        params.prompt = "explicit:imp*";
+        assert!(!check(&params));
+
+        params.prompt = "l?";
+        assert!(check(&params));
+
+        params.prompt = "l[3-9]";
+        assert!(!check(&params));
+
+        params.prompt = r"l\*";
+        assert!(!check(&params));
+    }
+
+    #[test]
+    fn check_works_with_normalized_labels() {
+        use crate::core::label::normalize::{Normalization, UnicodeForm};
+
+        let library = LabelLibrary::from_toml(
+            r#"
+            [Cat]
+            aliases = ["kitty"]
+        "#,
+        )
+        .unwrap()
+        .normalized(Normalization {
+            case_folding: true,
+            form: UnicodeForm::Nfc,
+            strip_diacritics: true,
+        })
+        .unwrap();
+
+        let mut document = Document::from_filename("KİTTY Café fn name");
+        document.prepare(&library);
+
+        let check_prompt = |prompt| {
+            check(&CheckParams {
+                prompt,
+                document: &document,
+                library: &library,
+            })
+        };
+
+        assert!(check_prompt("cat"));
+        assert!(check_prompt("CAT explicit:Kitty"));
+        assert!(check_prompt("cafe"));
+        assert!(check_prompt("CAF*"));
+        assert!(!check_prompt("dog"));
+    }
+
+    #[test]
+    fn check_ignores_derived_labels_in_explicit_terms() {
+        let library = LabelLibrary::from_toml(
+            r#"
+            [cat]
+
+            [[rules]]
+            path = "photos/**"
+            implies = ["photo"]
+        "#,
+        )
+        .unwrap();
+
+        let mut document = Document::from_filename("photos/cat fn a.jpg");
+        document.prepare(&library);
+
+        let check_prompt = |prompt| {
+            check(&CheckParams {
+                prompt,
+                document: &document,
+                library: &library,
+            })
+        };
+
+        assert!(check_prompt("photo explicit:cat"));
+        assert!(!check_prompt("explicit:photo"));
+        assert!(check_prompt("explicit:count=1"));
+        assert!(check_prompt("system:all-known"));
+    }
+
+    #[test]
+    fn check_works_with_escaped_patterns() {
+        let library = LabelLibrary::empty();
+
+        let document = Document::from_filename("l* fn name");
+
+        let params = CheckParams {
+            prompt: r"l\*",
+            document: &document,
+            library: &library,
+        };
+
+        assert!(check(&params));
+    }
 }
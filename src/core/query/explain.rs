@@ -0,0 +1,304 @@
+//! Explains why a document matches a query, like `tiger → implies cat → alias
+//! kitty` for the term `kitty`.
+
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+
+use super::{matchers, CheckParams, MatchContext, PSEUDO_DELIMITER};
+use crate::core::document::Document;
+use crate::core::label::{LabelLibrary, LabelSet};
+
+/// A step in the chain of relationships leading to a label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// Written in the filename.
+    Explicit(String),
+    /// Added by a file rule.
+    Derived(String),
+    /// Added by a label rule with the given query.
+    Rule {
+        when: String,
+        label: String,
+    },
+    Implies(String),
+    Parent(String),
+    Alias(String),
+    /// Name of the label the previous step is an alias of.
+    AliasOf(String),
+}
+
+impl Step {
+    pub fn label(&self) -> &str {
+        match self {
+            Step::Explicit(label)
+            | Step::Derived(label)
+            | Step::Rule { label, .. }
+            | Step::Implies(label)
+            | Step::Parent(label)
+            | Step::Alias(label)
+            | Step::AliasOf(label) => label,
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Explicit(label) => write!(f, "{}", label),
+            Step::Derived(label) => write!(f, "{} (derived)", label),
+            Step::Rule { when, label } => write!(f, "rule \"{}\" implies {}", when, label),
+            Step::Implies(label) => write!(f, "implies {}", label),
+            Step::Parent(label) => write!(f, "parent {}", label),
+            Step::Alias(label) => write!(f, "alias {}", label),
+            Step::AliasOf(label) => write!(f, "alias of {}", label),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TermExplanation {
+    pub term: String,
+    pub matched: bool,
+    /// How the label matched by the term was reached, starting from a label of
+    /// the document. Empty for pseudo labels and terms that didn't match.
+    pub derivation: Vec<Step>,
+}
+
+impl fmt::Display for TermExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let steps: Vec<String> = self.derivation.iter().map(|s| s.to_string()).collect();
+
+        match (self.matched, steps.is_empty()) {
+            (true, true) => write!(f, "{}: matched", self.term),
+            (true, false) => write!(f, "{}: {}", self.term, steps.join(" → ")),
+            (false, _) => write!(f, "{}: not matched", self.term),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub matched: bool,
+    pub terms: Vec<TermExplanation>,
+}
+
+/// Same as `check` but explaining every term instead of stopping at the first
+/// one that doesn't match.
+pub fn explain(params: &CheckParams) -> Explanation {
+    let CheckParams {
+        prompt,
+        document,
+        library,
+    } = params;
+
+    let mut extended_labels = document.labels.clone();
+    extended_labels.expand_with(library);
+
+    let context = MatchContext {
+        document,
+        labels: &extended_labels,
+        library,
+    };
+
+    let terms: Vec<TermExplanation> = prompt
+        .split_whitespace()
+        .map(|term| {
+            let matched = context.check_term(term);
+
+            let derivation = match (matched, label_argument(term)) {
+                (true, Some(label)) => derive(
+                    document,
+                    &extended_labels,
+                    library,
+                    &library.normalize(label),
+                ),
+                _ => Vec::new(),
+            };
+
+            TermExplanation {
+                term: term.to_string(),
+                matched,
+                derivation,
+            }
+        })
+        .collect();
+
+    Explanation {
+        matched: terms.iter().all(|t| t.matched),
+        terms,
+    }
+}
+
+/// Label matched by a term, if it matches a label at all.
+fn label_argument(term: &str) -> Option<&str> {
+    match term.split_once(PSEUDO_DELIMITER) {
+        None => Some(term),
+        Some((prefix, argument)) => match matchers::argument(prefix) {
+            Some(matchers::Argument::Label) if !matchers::is_count(argument) => Some(argument),
+            _ => None,
+        },
+    }
+}
+
+/// Finds the shortest chain of relationships from the labels of the document
+/// to a label matching `pattern`.
+fn derive(
+    document: &Document,
+    expanded: &LabelSet,
+    library: &LabelLibrary,
+    pattern: &str,
+) -> Vec<Step> {
+    let mut queue: VecDeque<Vec<Step>> = VecDeque::new();
+
+    let mut labels: Vec<&String> = document.labels.iter().collect();
+    labels.sort();
+
+    for label in labels {
+        if document.derived.inner_set().contains(label) {
+            queue.push_back(vec![Step::Derived(label.clone())]);
+        } else {
+            queue.push_back(vec![Step::Explicit(label.clone())]);
+        }
+    }
+
+    for rule in library.rules() {
+        if rule.matches_labels(expanded) {
+            let when = rule.when.clone().unwrap_or_default();
+
+            for label in rule.implies.iter() {
+                queue.push_back(vec![Step::Rule {
+                    when: when.clone(),
+                    label: label.clone(),
+                }]);
+            }
+        }
+    }
+
+    let mut visited: HashSet<String> = HashSet::new();
+
+    while let Some(path) = queue.pop_front() {
+        let label = path.last().expect("Paths are never empty").label();
+
+        if !visited.insert(label.to_string()) {
+            continue;
+        }
+
+        if LabelSet::from([label]).contains_pattern(pattern) {
+            return path;
+        }
+
+        let next = |step: Step| {
+            let mut path = path.clone();
+            path.push(step);
+            path
+        };
+
+        match library.resolve_known(label) {
+            None => {}
+            Some(name) if name != label => queue.push_back(next(Step::AliasOf(name.to_string()))),
+            Some(name) => {
+                for alias in library.get_aliases(name) {
+                    queue.push_back(next(Step::Alias(alias.clone())));
+                }
+
+                for implied in library.get_implies(name) {
+                    queue.push_back(next(Step::Implies(implied.clone())));
+                }
+
+                if let Some(parent) = library.get_parent(name) {
+                    queue.push_back(next(Step::Parent(parent.to_string())));
+                }
+            }
+        }
+    }
+
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::label::tests::setup_library;
+
+    fn explain_term(filename: &str, term: &str, library: &LabelLibrary) -> String {
+        let document = Document::from_filename(filename);
+
+        let explanation = explain(&CheckParams {
+            prompt: term,
+            document: &document,
+            library,
+        });
+
+        explanation.terms[0].to_string()
+    }
+
+    #[test]
+    fn explain_works() {
+        let library = setup_library();
+
+        assert_eq!(
+            explain_term("tiger fn a", "kitty", &library),
+            "kitty: tiger → implies cat → alias kitty"
+        );
+        assert_eq!(
+            explain_term("puppy fn a", "adorable", &library),
+            "adorable: puppy → alias of dog → implies pet → implies cute → alias adorable"
+        );
+        assert_eq!(explain_term("cat fn a", "cat", &library), "cat: cat");
+        assert_eq!(explain_term("cat fn a", "c?t", &library), "c?t: cat");
+        assert_eq!(
+            explain_term("cat fn a", "dog", &library),
+            "dog: not matched"
+        );
+        assert_eq!(
+            explain_term("cat fn a", "system:labeled", &library),
+            "system:labeled: matched"
+        );
+        assert_eq!(
+            explain_term("cat fn a", "explicit:cat", &library),
+            "explicit:cat: cat"
+        );
+    }
+
+    #[test]
+    fn explain_works_with_rules_and_parents() {
+        let library = LabelLibrary::from_toml(
+            r#"
+            [[rules]]
+            when = "cat meme"
+            implies = ["catmeme"]
+
+            [[rules]]
+            path = "photos/**"
+            implies = ["photo"]
+
+            [animal]
+
+            [cat]
+            parent = "animal"
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            explain_term("cat meme fn a", "catmeme", &library),
+            r#"catmeme: rule "cat meme" implies catmeme"#
+        );
+        assert_eq!(
+            explain_term("cat fn a", "animal", &library),
+            "animal: cat → parent animal"
+        );
+
+        let mut document = Document::from_filename("photos/x fn a");
+        document.apply_rules(&library);
+
+        let explanation = explain(&CheckParams {
+            prompt: "photo x",
+            document: &document,
+            library: &library,
+        });
+
+        assert!(explanation.matched);
+        assert_eq!(explanation.terms[0].to_string(), "photo: photo (derived)");
+    }
+}
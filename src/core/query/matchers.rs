@@ -0,0 +1,156 @@
+//! Pseudo label matchers, used by query terms in the form `prefix:argument`.
+//!
+//! To add a new kind of pseudo label, create a module exposing a `matches`
+//! function and register it with its prefix in `REGISTRY`.
+
+use super::{MatchContext, PSEUDO_DELIMITER};
+
+mod compare;
+mod count;
+mod explicit;
+mod ext;
+mod file_type;
+mod modified;
+mod name;
+mod name_regex;
+mod not;
+mod path;
+mod size;
+mod system;
+mod unknown;
+
+/// Decides if the document in the context matches the argument given after
+/// the prefix of the pseudo label.
+pub type Matcher = fn(&MatchContext, &str) -> bool;
+
+/// What the argument of a pseudo label refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Argument {
+    /// Another query term, like in `not:`.
+    Term,
+    /// A label, like in `explicit:`.
+    Label,
+    /// Anything else, specific to the matcher.
+    Other,
+}
+
+struct Entry {
+    prefix: &'static str,
+    matcher: Matcher,
+    argument: Argument,
+    /// Reads the file system, so it's better to evaluate it last.
+    reads_metadata: bool,
+}
+
+const fn entry(prefix: &'static str, matcher: Matcher) -> Entry {
+    Entry {
+        prefix,
+        matcher,
+        argument: Argument::Other,
+        reads_metadata: false,
+    }
+}
+
+impl Entry {
+    const fn taking(mut self, argument: Argument) -> Self {
+        self.argument = argument;
+        self
+    }
+
+    const fn reading_metadata(mut self) -> Self {
+        self.reads_metadata = true;
+        self
+    }
+}
+
+const REGISTRY: &[Entry] = &[
+    entry("system", system::matches),
+    entry("not", not::matches).taking(Argument::Term),
+    entry("explicit", explicit::matches).taking(Argument::Label),
+    entry("unknown", unknown::matches).taking(Argument::Label),
+    entry("name", name::matches),
+    entry("name~", name_regex::matches),
+    entry("ext", ext::matches),
+    entry("path", path::matches),
+    entry("size", size::matches).reading_metadata(),
+    entry("modified", modified::matches).reading_metadata(),
+    entry("type", file_type::matches).reading_metadata(),
+];
+
+fn find_entry(prefix: &str) -> Option<&'static Entry> {
+    REGISTRY.iter().find(|e| e.prefix == prefix)
+}
+
+pub fn find(prefix: &str) -> Option<Matcher> {
+    find_entry(prefix).map(|e| e.matcher)
+}
+
+pub fn argument(prefix: &str) -> Option<Argument> {
+    find_entry(prefix).map(|e| e.argument)
+}
+
+/// Returns `true` if the argument is a label count expression like `count<3`
+/// instead of a label.
+pub fn is_count(argument: &str) -> bool {
+    count::parse(argument).is_some()
+}
+
+/// Prefixes of every registered pseudo label.
+pub fn prefixes() -> Vec<&'static str> {
+    REGISTRY.iter().map(|e| e.prefix).collect()
+}
+
+/// Returns `true` if checking the term requires reading file metadata,
+/// including pseudo labels wrapped by others like `not:size:>1M`.
+pub fn reads_metadata(term: &str) -> bool {
+    let mut rest = term;
+
+    while let Some((prefix, suffix)) = rest.split_once(PSEUDO_DELIMITER) {
+        if find_entry(prefix).is_some_and(|e| e.reads_metadata) {
+            return true;
+        }
+
+        rest = suffix;
+    }
+
+    false
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::core::{
+        document::Document,
+        label::{LabelLibrary, LabelSet},
+    };
+
+    /// Checks a term against a document without labels nor library.
+    pub fn check_document(document: &Document, term: &str) -> bool {
+        let library = LabelLibrary::empty();
+        let labels = LabelSet::empty();
+
+        let context = MatchContext {
+            document,
+            labels: &labels,
+            library: &library,
+        };
+
+        context.check_term(term)
+    }
+
+    #[test]
+    fn find_works() {
+        assert!(find("system").is_some());
+        assert!(find("name~").is_some());
+        assert!(find("missing").is_none());
+        assert!(prefixes().contains(&"path"));
+    }
+
+    #[test]
+    fn reads_metadata_works() {
+        assert!(reads_metadata("size:>1M"));
+        assert!(reads_metadata("not:type:dir"));
+        assert!(!reads_metadata("not:name:*.pdf"));
+        assert!(!reads_metadata("size"));
+    }
+}
@@ -0,0 +1,78 @@
+//! Comparison operators shared by the matchers taking numeric-like arguments.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+impl Comparison {
+    /// Splits the leading operator (`<`, `<=`, `=`, `>=`, `>`) from the
+    /// argument. Without operator, `=` is assumed.
+    pub fn parse(argument: &str) -> (Self, &str) {
+        let operators = [
+            ("<=", Comparison::Le),
+            (">=", Comparison::Ge),
+            ("<", Comparison::Lt),
+            (">", Comparison::Gt),
+            ("=", Comparison::Eq),
+        ];
+
+        for (operator, comparison) in operators {
+            if let Some(rest) = argument.strip_prefix(operator) {
+                return (comparison, rest);
+            }
+        }
+
+        (Comparison::Eq, argument)
+    }
+
+    pub fn test<T: PartialOrd>(self, value: T, reference: T) -> bool {
+        match self {
+            Comparison::Lt => value < reference,
+            Comparison::Le => value <= reference,
+            Comparison::Eq => value == reference,
+            Comparison::Ge => value >= reference,
+            Comparison::Gt => value > reference,
+        }
+    }
+
+    /// Like `test` but the reference is the half open range `start..end`, so
+    /// for example `=` means inside the range and `>` means after it.
+    pub fn test_range<T: PartialOrd>(self, value: T, start: T, end: T) -> bool {
+        match self {
+            Comparison::Lt => value < start,
+            Comparison::Le => value < end,
+            Comparison::Eq => start <= value && value < end,
+            Comparison::Ge => value >= start,
+            Comparison::Gt => value >= end,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_works() {
+        assert_eq!(Comparison::parse("<=3"), (Comparison::Le, "3"));
+        assert_eq!(Comparison::parse(">3"), (Comparison::Gt, "3"));
+        assert_eq!(Comparison::parse("=3"), (Comparison::Eq, "3"));
+        assert_eq!(Comparison::parse("3"), (Comparison::Eq, "3"));
+    }
+
+    #[test]
+    fn test_range_works() {
+        assert!(Comparison::Lt.test_range(1, 2, 4));
+        assert!(!Comparison::Lt.test_range(2, 2, 4));
+        assert!(Comparison::Le.test_range(3, 2, 4));
+        assert!(Comparison::Eq.test_range(2, 2, 4));
+        assert!(!Comparison::Eq.test_range(4, 2, 4));
+        assert!(Comparison::Gt.test_range(4, 2, 4));
+        assert!(!Comparison::Gt.test_range(3, 2, 4));
+    }
+}
@@ -0,0 +1,42 @@
+//! Label count expressions like `count<3`, shared by the matchers counting
+//! labels.
+
+use super::compare::Comparison;
+
+const COUNT_KEYWORD: &str = "count";
+
+/// Parses a count expression. It requires an explicit operator, so `count`
+/// alone can still be used as a label name.
+pub fn parse(argument: &str) -> Option<(Comparison, usize)> {
+    let expression = argument.strip_prefix(COUNT_KEYWORD)?;
+
+    if !expression.starts_with(['<', '=', '>']) {
+        return None;
+    }
+
+    let (comparison, number) = Comparison::parse(expression);
+    Some((comparison, number.parse().ok()?))
+}
+
+/// Checks the count expression in `argument` against `count`, or returns
+/// `None` if the argument is not a count expression.
+pub fn check(argument: &str, count: usize) -> Option<bool> {
+    let (comparison, reference) = parse(argument)?;
+    Some(comparison.test(count, reference))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_works() {
+        assert_eq!(parse("count<3"), Some((Comparison::Lt, 3)));
+        assert_eq!(parse("count>=10"), Some((Comparison::Ge, 10)));
+        assert_eq!(parse("count=1"), Some((Comparison::Eq, 1)));
+        assert_eq!(parse("count"), None);
+        assert_eq!(parse("count3"), None);
+        assert_eq!(parse("count<x"), None);
+        assert_eq!(parse("counter"), None);
+    }
+}
@@ -0,0 +1,20 @@
+use super::count;
+use crate::core::label::LabelSet;
+use crate::core::query::MatchContext;
+
+/// Matches labels written in the filename, ignoring aliases, implications and
+/// labels derived by file rules.
+///
+/// Also supports count expressions like `explicit:count<3`.
+pub fn matches(context: &MatchContext, argument: &str) -> bool {
+    let labels: LabelSet = context
+        .document
+        .explicit_labels()
+        .map(|l| l.as_str())
+        .collect();
+
+    match count::check(argument, labels.inner_set().len()) {
+        Some(result) => result,
+        None => context.has_label(&labels, argument),
+    }
+}
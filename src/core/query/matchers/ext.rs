@@ -0,0 +1,26 @@
+use crate::core::query::MatchContext;
+
+/// Matches the extension of the document name, case insensitively and with or
+/// without the leading dot.
+pub fn matches(context: &MatchContext, argument: &str) -> bool {
+    context.document.has_extension(argument)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::document::Document;
+    use crate::core::query::matchers::tests::check_document;
+
+    #[test]
+    fn matches_works() {
+        let document = Document::from_filename("l1 fn photo.JPG");
+
+        assert!(check_document(&document, "ext:jpg"));
+        assert!(check_document(&document, "ext:.jpg"));
+        assert!(!check_document(&document, "ext:png"));
+        assert!(!check_document(&document, "ext:"));
+
+        let document = Document::from_filename("l1 fn README");
+        assert!(check_document(&document, "ext:"));
+    }
+}
@@ -0,0 +1,37 @@
+use crate::core::document::metadata::FileKind;
+use crate::core::query::MatchContext;
+
+/// Matches the kind of file: `type:file`, `type:dir`, `type:symlink` or
+/// `type:other`. Symlinks are not followed.
+pub fn matches(context: &MatchContext, argument: &str) -> bool {
+    let kind = match argument {
+        "file" => FileKind::File,
+        "dir" => FileKind::Dir,
+        "symlink" => FileKind::Symlink,
+        "other" => FileKind::Other,
+        _ => return false,
+    };
+
+    match context.document.metadata() {
+        Some(metadata) => metadata.kind == kind,
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::document::Document;
+    use crate::core::query::matchers::tests::check_document;
+
+    #[test]
+    fn matches_works() {
+        let document = Document::from_filename("test_dir/files");
+
+        assert!(check_document(&document, "type:dir"));
+        assert!(!check_document(&document, "type:file"));
+        assert!(!check_document(&document, "type:unknown"));
+
+        let document = Document::from_filename("test_dir/files/a.txt");
+        assert!(check_document(&document, "type:file"));
+    }
+}
@@ -0,0 +1,97 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::compare::Comparison;
+use crate::core::query::MatchContext;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Matches the modification date of the file, like `modified:<2024-01-01`.
+///
+/// Dates are `YYYY-MM-DD` in UTC and compared with day granularity, so
+/// `modified:2024-01-01` matches anything modified during that day and
+/// `modified:>2024-01-01` anything modified after it.
+pub fn matches(context: &MatchContext, argument: &str) -> bool {
+    let (comparison, date) = Comparison::parse(argument);
+
+    let start = match parse_date(date) {
+        Some(start) => start,
+        None => return false,
+    };
+
+    let end = start + Duration::from_secs(SECONDS_PER_DAY);
+
+    match context.document.metadata().and_then(|m| m.modified) {
+        Some(modified) => comparison.test_range(modified, start, end),
+        None => false,
+    }
+}
+
+/// Parses a `YYYY-MM-DD` date into the time at the start of that day in UTC.
+fn parse_date(date: &str) -> Option<SystemTime> {
+    let mut parts = date.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: i64 = parts.next()?.parse().ok()?;
+    let day: i64 = parts.next()?.parse().ok()?;
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let seconds = days_from_civil(year, month, day) * SECONDS_PER_DAY as i64;
+
+    if seconds >= 0 {
+        UNIX_EPOCH.checked_add(Duration::from_secs(seconds as u64))
+    } else {
+        UNIX_EPOCH.checked_sub(Duration::from_secs(seconds.unsigned_abs()))
+    }
+}
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+///
+/// See http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_from_march = (month + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::document::Document;
+    use crate::core::query::matchers::tests::check_document;
+
+    #[test]
+    fn days_from_civil_works() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(days_from_civil(2024, 1, 1), 19723);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+    }
+
+    #[test]
+    fn parse_date_works() {
+        assert_eq!(
+            parse_date("1970-01-02"),
+            Some(UNIX_EPOCH + Duration::from_secs(SECONDS_PER_DAY))
+        );
+        assert_eq!(parse_date("2024-13-01"), None);
+        assert_eq!(parse_date("2024-01"), None);
+        assert_eq!(parse_date("yesterday"), None);
+    }
+
+    #[test]
+    fn matches_works() {
+        let document = Document::from_filename("Cargo.toml");
+
+        assert!(check_document(&document, "modified:>2000-01-01"));
+        assert!(check_document(&document, "modified:<9999-01-01"));
+        assert!(!check_document(&document, "modified:<2000-01-01"));
+        assert!(!check_document(&document, "modified:invalid"));
+    }
+}
@@ -0,0 +1,23 @@
+use crate::core::query::MatchContext;
+use crate::utils::glob::glob_match;
+
+/// Matches the name of the document (without labels) against a glob pattern.
+pub fn matches(context: &MatchContext, argument: &str) -> bool {
+    glob_match(argument, &context.document.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::document::Document;
+    use crate::core::query::matchers::tests::check_document;
+
+    #[test]
+    fn matches_works() {
+        let document = Document::from_filename("docs/l1 fn report.final.pdf");
+
+        assert!(check_document(&document, "name:*.pdf"));
+        assert!(check_document(&document, "name:report.*"));
+        assert!(!check_document(&document, "name:*.txt"));
+        assert!(!check_document(&document, "name:l1*"));
+    }
+}
@@ -0,0 +1,25 @@
+use crate::core::query::MatchContext;
+use crate::utils::regex_cache;
+
+/// Matches the name of the document (without labels) against a regex.
+///
+/// Invalid regexes never match.
+pub fn matches(context: &MatchContext, argument: &str) -> bool {
+    regex_cache::is_match(argument, &context.document.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::document::Document;
+    use crate::core::query::matchers::tests::check_document;
+
+    #[test]
+    fn matches_works() {
+        let document = Document::from_filename("l1 fn IMG_2023.jpg");
+
+        assert!(check_document(&document, r"name~:^IMG_\d{4}"));
+        assert!(check_document(&document, "name~:jpg$"));
+        assert!(!check_document(&document, "name~:^l1"));
+        assert!(!check_document(&document, "name~:("));
+    }
+}
@@ -0,0 +1,6 @@
+use crate::core::query::MatchContext;
+
+/// Negates any other term, including pseudo labels.
+pub fn matches(context: &MatchContext, argument: &str) -> bool {
+    !context.check_term(argument)
+}
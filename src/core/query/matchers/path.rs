@@ -0,0 +1,25 @@
+use crate::core::query::MatchContext;
+use crate::utils::glob::glob_match;
+
+/// Matches the path of the document, relative to the root, against a glob
+/// pattern.
+pub fn matches(context: &MatchContext, argument: &str) -> bool {
+    glob_match(argument, &context.document.path.to_string_lossy())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::document::Document;
+    use crate::core::query::matchers::tests::check_document;
+
+    #[test]
+    fn matches_works() {
+        let document = Document::from_filename("projects/alpha/l1 fn plan.md");
+
+        assert!(check_document(&document, "path:projects/**"));
+        assert!(check_document(&document, "path:**/*.md"));
+        assert!(check_document(&document, "path:projects/*/*"));
+        assert!(!check_document(&document, "path:projects/*"));
+        assert!(!check_document(&document, "path:other/**"));
+    }
+}
@@ -0,0 +1,77 @@
+use super::compare::Comparison;
+use crate::core::query::MatchContext;
+
+/// Matches the size of the file, like `size:>10M` or `size:<=512K`.
+///
+/// Units are powers of 1024 and case insensitive: `B`, `K`, `M`, `G` and `T`,
+/// optionally followed by `B` or `iB`.
+pub fn matches(context: &MatchContext, argument: &str) -> bool {
+    let (comparison, size) = Comparison::parse(argument);
+
+    let reference = match parse_size(size) {
+        Some(reference) => reference,
+        None => return false,
+    };
+
+    match context.document.metadata() {
+        Some(metadata) => comparison.test(metadata.size, reference),
+        None => false,
+    }
+}
+
+fn parse_size(size: &str) -> Option<u64> {
+    let digits_end = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+
+    let (number, unit) = size.split_at(digits_end);
+    let number: f64 = number.parse().ok()?;
+
+    let unit = unit.to_ascii_uppercase();
+    let unit = unit
+        .strip_suffix("IB")
+        .or_else(|| unit.strip_suffix('B'))
+        .unwrap_or(&unit);
+
+    let exponent = match unit {
+        "" => 0,
+        "K" => 1,
+        "M" => 2,
+        "G" => 3,
+        "T" => 4,
+        _ => return None,
+    };
+
+    Some((number * 1024f64.powi(exponent)) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::document::Document;
+    use crate::core::query::matchers::tests::check_document;
+
+    #[test]
+    fn parse_size_works() {
+        assert_eq!(parse_size("10"), Some(10));
+        assert_eq!(parse_size("10B"), Some(10));
+        assert_eq!(parse_size("2K"), Some(2048));
+        assert_eq!(parse_size("1.5kb"), Some(1536));
+        assert_eq!(parse_size("1MiB"), Some(1024 * 1024));
+        assert_eq!(parse_size("1G"), Some(1024 * 1024 * 1024));
+        assert_eq!(parse_size("1X"), None);
+        assert_eq!(parse_size("K"), None);
+    }
+
+    #[test]
+    fn matches_works() {
+        let document = Document::from_filename("Cargo.toml");
+
+        assert!(check_document(&document, "size:>10"));
+        assert!(check_document(&document, "size:<1M"));
+        assert!(!check_document(&document, "size:>1M"));
+
+        let document = Document::from_filename("test_dir/files/missing");
+        assert!(!check_document(&document, "size:>=0"));
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashSet;
+
+use super::count;
+use crate::core::query::MatchContext;
+
+pub fn matches(context: &MatchContext, argument: &str) -> bool {
+    match argument {
+        "unlabeled" => context.labels.is_empty(),
+        "labeled" => !context.labels.is_empty(),
+        "has-unknown" => explicit_labels(context).any(|l| !context.library.is_known(l)),
+        "all-known" => explicit_labels(context).all(|l| context.library.is_known(l)),
+        _ => count::check(argument, expanded_count(context)).unwrap_or(false),
+    }
+}
+
+fn explicit_labels<'a>(context: &'a MatchContext) -> impl Iterator<Item = &'a String> {
+    context.document.explicit_labels()
+}
+
+/// Number of different labels after expansion. Aliases of the same label are
+/// counted once.
+fn expanded_count(context: &MatchContext) -> usize {
+    context
+        .labels
+        .iter()
+        .map(|l| context.library.resolve(l))
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::document::Document;
+    use crate::core::label::tests::setup_library;
+    use crate::core::query::{check, CheckParams};
+
+    #[test]
+    fn known_works() {
+        let library = setup_library();
+
+        let check_prompt = |prompt, filename| {
+            check(&CheckParams {
+                prompt,
+                document: &Document::from_filename(filename),
+                library: &library,
+            })
+        };
+
+        assert!(check_prompt("system:has-unknown", "cat stray fn name.ext"));
+        assert!(!check_prompt("system:all-known", "cat stray fn name.ext"));
+        assert!(!check_prompt("system:has-unknown", "cat kitty fn name.ext"));
+        assert!(check_prompt("system:all-known", "cat kitty fn name.ext"));
+        assert!(check_prompt("system:all-known", "name.ext"));
+    }
+
+    #[test]
+    fn count_works() {
+        let library = setup_library();
+
+        // Expands into tiger, cat, pet and cute, plus the unknown label.
+        let document = Document::from_filename("tiger kitty unknown fn name.ext");
+
+        let check_prompt = |prompt| {
+            check(&CheckParams {
+                prompt,
+                document: &document,
+                library: &library,
+            })
+        };
+
+        assert!(check_prompt("system:count=5"));
+        assert!(check_prompt("system:count>4"));
+        assert!(!check_prompt("system:count<5"));
+        assert!(check_prompt("explicit:count=3"));
+        assert!(check_prompt("explicit:count<=3 system:count>=3"));
+        assert!(!check_prompt("explicit:count<3"));
+        assert!(!check_prompt("system:count"));
+    }
+}
@@ -0,0 +1,72 @@
+use crate::core::label::LabelSet;
+use crate::core::query::MatchContext;
+
+/// Matches documents explicitly labeled with the given label, as long as the
+/// label is not known by the library. Patterns only match unknown labels.
+pub fn matches(context: &MatchContext, argument: &str) -> bool {
+    let labels: LabelSet = context
+        .document
+        .explicit_labels()
+        .filter(|l| !context.library.is_known(l))
+        .map(|l| l.as_str())
+        .collect();
+
+    context.has_label(&labels, argument)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::document::Document;
+    use crate::core::label::tests::setup_library;
+    use crate::core::label::LabelLibrary;
+    use crate::core::query::{check, CheckParams};
+
+    #[test]
+    fn matches_works() {
+        let library = setup_library();
+        let document = Document::from_filename("cat stray fn name.ext");
+
+        let check_prompt = |prompt| {
+            check(&CheckParams {
+                prompt,
+                document: &document,
+                library: &library,
+            })
+        };
+
+        assert!(check_prompt("unknown:stray"));
+        assert!(!check_prompt("unknown:cat"));
+        assert!(!check_prompt("unknown:other"));
+
+        assert!(check_prompt("unknown:s*"));
+        assert!(!check_prompt("unknown:c*"));
+    }
+
+    #[test]
+    fn matches_ignores_derived_labels() {
+        let library = LabelLibrary::from_toml(
+            r#"
+            [[rules]]
+            ext = "jpg"
+            implies = ["photo"]
+        "#,
+        )
+        .unwrap();
+
+        let mut document = Document::from_filename("stray fn name.jpg");
+        document.prepare(&library);
+
+        let check_prompt = |prompt| {
+            check(&CheckParams {
+                prompt,
+                document: &document,
+                library: &library,
+            })
+        };
+
+        assert!(check_prompt("photo"));
+        assert!(check_prompt("unknown:stray"));
+        assert!(!check_prompt("unknown:photo"));
+        assert!(!check_prompt("unknown:p*"));
+    }
+}
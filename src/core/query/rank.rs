@@ -0,0 +1,188 @@
+//! Scores documents by how well they match a query instead of requiring every
+//! term to match.
+
+use std::cmp::Reverse;
+
+use super::{CheckParams, MatchContext, PSEUDO_DELIMITER};
+use crate::core::document::Document;
+use crate::core::label::{LabelLibrary, LabelSet};
+use crate::utils::text::closest_matches;
+
+/// Score of a label written in the filename, or an alias of it.
+pub const EXPLICIT_SCORE: u32 = 4;
+/// Score of a label reached through implications, parents or rules, and of a
+/// matching pseudo label other than `not:`.
+pub const IMPLIED_SCORE: u32 = 2;
+/// Score of a term only found in the name of the file.
+pub const FUZZY_NAME_SCORE: u32 = 1;
+
+const NOT_PREFIX: &str = "not:";
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RankOptions {
+    /// Terms not matching any label can still match words of the name, with
+    /// small typos allowed.
+    pub fuzzy_name: bool,
+}
+
+/// Sum of the scores of every term of the query matched by the document.
+///
+/// Negated terms don't score, they are filters instead: the score is 0 unless
+/// all of them match.
+pub fn score(params: &CheckParams, options: &RankOptions) -> u32 {
+    let CheckParams {
+        prompt,
+        document,
+        library,
+    } = params;
+
+    let mut extended_labels = document.labels.clone();
+    extended_labels.expand_with(library);
+
+    let explicit_labels: LabelSet = document.explicit_labels().map(|l| l.as_str()).collect();
+    let explicit_labels = with_aliases(&explicit_labels, library);
+
+    let context = MatchContext {
+        document,
+        labels: &extended_labels,
+        library,
+    };
+
+    let (negations, terms): (Vec<&str>, Vec<&str>) = prompt
+        .split_whitespace()
+        .partition(|term| term.starts_with(NOT_PREFIX));
+
+    if !negations.into_iter().all(|term| context.check_term(term)) {
+        return 0;
+    }
+
+    terms
+        .into_iter()
+        .map(|term| {
+            let is_label = !term.contains(PSEUDO_DELIMITER);
+
+            if is_label && context.has_label(&explicit_labels, term) {
+                EXPLICIT_SCORE
+            } else if context.check_term(term) {
+                IMPLIED_SCORE
+            } else if is_label && options.fuzzy_name && fuzzy_match(&document.name, term) {
+                FUZZY_NAME_SCORE
+            } else {
+                0
+            }
+        })
+        .sum()
+}
+
+/// Documents matching at least one term, best first. Documents with the same
+/// score keep their order.
+pub fn rank<'a>(
+    documents: &'a [Document],
+    prompt: &str,
+    library: &LabelLibrary,
+    options: &RankOptions,
+) -> Vec<(&'a Document, u32)> {
+    let mut ranked: Vec<(&Document, u32)> = documents
+        .iter()
+        .map(|document| {
+            let params = CheckParams {
+                prompt,
+                document,
+                library,
+            };
+
+            (document, score(&params, options))
+        })
+        .filter(|(_, score)| *score > 0)
+        .collect();
+
+    ranked.sort_by_key(|(_, score)| Reverse(*score));
+    ranked
+}
+
+/// The labels plus the name and aliases of the ones that are known.
+fn with_aliases(labels: &LabelSet, library: &LabelLibrary) -> LabelSet {
+    labels
+        .iter()
+        .flat_map(|label| {
+            let name = library.resolve(label);
+
+            [label.as_str(), name]
+                .into_iter()
+                .chain(library.get_aliases(name).iter().map(|a| a.as_str()))
+        })
+        .collect()
+}
+
+/// Checks if the name contains the term, or a word close to it, ignoring case.
+pub fn fuzzy_match(name: &str, term: &str) -> bool {
+    let name = name.to_lowercase();
+    let term = term.to_lowercase();
+
+    if name.contains(&term) {
+        return true;
+    }
+
+    let words = name.split(|c: char| !c.is_alphanumeric());
+    !closest_matches(&term, words, 1).is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::label::tests::setup_library;
+
+    #[test]
+    fn score_works() {
+        let library = setup_library();
+        let document = Document::from_filename("kitty meme fn Funny Picture.jpg");
+
+        let score_of = |prompt, fuzzy_name| {
+            let params = CheckParams {
+                prompt,
+                document: &document,
+                library: &library,
+            };
+
+            score(&params, &RankOptions { fuzzy_name })
+        };
+
+        assert_eq!(score_of("cat", false), EXPLICIT_SCORE);
+        assert_eq!(score_of("pet", false), IMPLIED_SCORE);
+        assert_eq!(score_of("system:labeled", false), IMPLIED_SCORE);
+        assert_eq!(score_of("dog", false), 0);
+        assert_eq!(
+            score_of("cat pet dog", false),
+            EXPLICIT_SCORE + IMPLIED_SCORE
+        );
+
+        assert_eq!(score_of("picture", false), 0);
+        assert_eq!(score_of("picture", true), FUZZY_NAME_SCORE);
+        assert_eq!(score_of("pictrue", true), FUZZY_NAME_SCORE);
+        assert_eq!(score_of("landscape", true), 0);
+
+        // Negations only filter.
+        assert_eq!(score_of("not:dog", false), 0);
+        assert_eq!(score_of("dog not:dog", false), 0);
+        assert_eq!(score_of("cat not:dog", false), EXPLICIT_SCORE);
+        assert_eq!(score_of("cat not:meme", false), 0);
+    }
+
+    #[test]
+    fn rank_works() {
+        let library = setup_library();
+        let documents = vec![
+            Document::from_filename("dog fn a"),
+            Document::from_filename("pet fn b"),
+            Document::from_filename("tiger fn c"),
+            Document::from_filename("cat pet fn d"),
+        ];
+
+        let ranked: Vec<(&str, u32)> = rank(&documents, "cat pet", &library, &Default::default())
+            .into_iter()
+            .map(|(d, score)| (d.name.as_str(), score))
+            .collect();
+
+        assert_eq!(ranked, vec![("d", 8), ("b", 4), ("c", 4), ("a", 2)]);
+    }
+}
@@ -0,0 +1,209 @@
+//! Detection of query terms that most likely won't match anything, like
+//! typos of label names.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use super::matchers::{self, Argument};
+use super::PSEUDO_DELIMITER;
+use crate::core::{document::Document, label::LabelLibrary};
+use crate::utils::glob::{glob_match, is_glob, unescape};
+use crate::utils::text::closest_matches;
+
+const MAX_SUGGESTIONS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryIssue {
+    /// A label neither known by the library nor present on any document.
+    UnknownLabel {
+        label: String,
+        suggestions: Vec<String>,
+    },
+    /// A pseudo label prefix that doesn't exist.
+    UnknownPrefix {
+        prefix: String,
+        suggestions: Vec<String>,
+    },
+}
+
+impl fmt::Display for QueryIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let suggestions = match self {
+            QueryIssue::UnknownLabel { label, suggestions } => {
+                write!(f, "Unknown label \"{}\"", label)?;
+                suggestions.iter().map(|s| format!("\"{}\"", s)).collect()
+            }
+            QueryIssue::UnknownPrefix {
+                prefix,
+                suggestions,
+            } => {
+                write!(f, "Unknown pseudo label \"{}:\"", prefix)?;
+                suggestions
+                    .iter()
+                    .map(|s| format!("\"{}:\"", s))
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        if !suggestions.is_empty() {
+            write!(f, ", did you mean {}?", suggestions.join(" or "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds the terms of the prompt referencing labels that are neither known by
+/// the library nor present on any of the documents, and the unknown pseudo
+/// labels.
+pub fn find_issues(
+    prompt: &str,
+    library: &LabelLibrary,
+    documents: &[Document],
+) -> Vec<QueryIssue> {
+    let mut candidates: HashSet<&str> = HashSet::new();
+
+    for name in library.label_names() {
+        candidates.insert(name);
+        candidates.extend(library.get_aliases(name).iter().map(|a| a.as_str()));
+    }
+
+    for rule in library.rules() {
+        candidates.extend(rule.implies.iter().map(|l| l.as_str()));
+    }
+
+    for document in documents {
+        candidates.extend(document.labels.iter().map(|l| l.as_str()));
+    }
+
+    let mut candidates: Vec<&str> = candidates.into_iter().collect();
+    candidates.sort();
+
+    prompt
+        .split_whitespace()
+        .filter_map(|term| find_term_issue(term, &candidates, library))
+        .collect()
+}
+
+fn find_term_issue(term: &str, candidates: &[&str], library: &LabelLibrary) -> Option<QueryIssue> {
+    match term.split_once(PSEUDO_DELIMITER) {
+        Some((prefix, argument)) => match matchers::argument(prefix) {
+            Some(Argument::Term) => find_term_issue(argument, candidates, library),
+            Some(Argument::Label) => find_label_issue(argument, candidates, library),
+            Some(Argument::Other) => None,
+            None => Some(QueryIssue::UnknownPrefix {
+                prefix: prefix.to_string(),
+                suggestions: to_strings(closest_matches(
+                    prefix,
+                    matchers::prefixes(),
+                    MAX_SUGGESTIONS,
+                )),
+            }),
+        },
+        None => find_label_issue(term, candidates, library),
+    }
+}
+
+fn find_label_issue(
+    original: &str,
+    candidates: &[&str],
+    library: &LabelLibrary,
+) -> Option<QueryIssue> {
+    if original.is_empty() || matchers::is_count(original) {
+        return None;
+    }
+
+    // Candidates are already normalized.
+    let label = library.normalize(original);
+    let label = label.as_ref();
+
+    if is_glob(label) {
+        if candidates.iter().any(|c| glob_match(label, c)) {
+            return None;
+        }
+
+        // Suggesting close patterns is not worth it.
+        return Some(QueryIssue::UnknownLabel {
+            label: original.to_string(),
+            suggestions: Vec::new(),
+        });
+    }
+
+    let label = unescape(label);
+    let label = label.as_ref();
+
+    if candidates.contains(&label) {
+        return None;
+    }
+
+    Some(QueryIssue::UnknownLabel {
+        label: unescape(original).to_string(),
+        suggestions: to_strings(closest_matches(
+            label,
+            candidates.iter().copied(),
+            MAX_SUGGESTIONS,
+        )),
+    })
+}
+
+fn to_strings(strs: Vec<&str>) -> Vec<String> {
+    strs.into_iter().map(|s| s.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::label::tests::setup_library;
+
+    #[test]
+    fn find_issues_works() {
+        let library = setup_library();
+        let documents = vec![Document::from_filename("cat wallpaper fn a.png")];
+
+        let issues = find_issues(
+            "catt kitty wallpaper not:explicit:dgo sytem:labeled name:x size:>1 explicit:count<2 wall* zz*",
+            &library,
+            &documents,
+        );
+
+        assert_eq!(
+            issues,
+            vec![
+                QueryIssue::UnknownLabel {
+                    label: "catt".to_string(),
+                    suggestions: vec!["cat".to_string()],
+                },
+                QueryIssue::UnknownLabel {
+                    label: "dgo".to_string(),
+                    suggestions: vec!["dog".to_string()],
+                },
+                QueryIssue::UnknownPrefix {
+                    prefix: "sytem".to_string(),
+                    suggestions: vec!["system".to_string()],
+                },
+                QueryIssue::UnknownLabel {
+                    label: "zz*".to_string(),
+                    suggestions: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn display_works() {
+        let issue = QueryIssue::UnknownLabel {
+            label: "catt".to_string(),
+            suggestions: vec!["cat".to_string(), "cats".to_string()],
+        };
+        assert_eq!(
+            issue.to_string(),
+            "Unknown label \"catt\", did you mean \"cat\" or \"cats\"?"
+        );
+
+        let issue = QueryIssue::UnknownPrefix {
+            prefix: "foo".to_string(),
+            suggestions: vec![],
+        };
+        assert_eq!(issue.to_string(), "Unknown pseudo label \"foo:\"");
+    }
+}
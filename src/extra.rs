@@ -1 +1,7 @@
+pub mod args;
 pub mod input;
+pub mod listing;
+pub mod output;
+pub mod stats;
+pub mod suggest;
+pub mod walk;
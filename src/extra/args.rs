@@ -0,0 +1,75 @@
+/// Removes every occurrence of any of the given flags from `args`.
+///
+/// Returns `true` if at least one was present.
+pub fn take_flag(args: &mut Vec<String>, names: &[&str]) -> bool {
+    let len = args.len();
+    args.retain(|a| !names.contains(&a.as_str()));
+    args.len() != len
+}
+
+/// Removes the last occurrence of any of the given options and its value from
+/// `args`, returning the value.
+///
+/// Both `--option value` and `--option=value` forms are supported.
+pub fn take_value(args: &mut Vec<String>, names: &[&str]) -> Option<String> {
+    let mut value = None;
+    let mut i = 0;
+
+    while i < args.len() {
+        let arg = &args[i];
+
+        if names.contains(&arg.as_str()) {
+            if i + 1 >= args.len() {
+                panic!("Missing value for option {}", arg);
+            }
+
+            value = Some(args.remove(i + 1));
+            args.remove(i);
+            continue;
+        }
+
+        let inline = names.iter().find_map(|name| {
+            arg.strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix('='))
+                .map(|v| v.to_string())
+        });
+
+        if inline.is_some() {
+            value = inline;
+            args.remove(i);
+            continue;
+        }
+
+        i += 1;
+    }
+
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(input: &[&str]) -> Vec<String> {
+        input.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn take_flag_works() {
+        let mut input = args(&["a", "-x", "b", "--one", "-x"]);
+
+        assert!(take_flag(&mut input, &["-x", "--other"]));
+        assert!(!take_flag(&mut input, &["--missing"]));
+        assert_eq!(input, args(&["a", "b", "--one"]));
+    }
+
+    #[test]
+    fn take_value_works() {
+        let mut input = args(&["a", "--depth", "3", "b", "--depth=4", "-n", "5"]);
+
+        assert_eq!(take_value(&mut input, &["--depth"]), Some("4".to_string()));
+        assert_eq!(take_value(&mut input, &["-n"]), Some("5".to_string()));
+        assert_eq!(take_value(&mut input, &["--missing"]), None);
+        assert_eq!(input, args(&["a", "b"]));
+    }
+}
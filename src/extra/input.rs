@@ -2,17 +2,48 @@ use std::io;
 use std::path::PathBuf;
 
 use crate::core::document::Document;
-use crate::utils::fs::get_unique_target;
+use crate::core::label::LabelLibrary;
+use crate::utils::fs::{get_unique_target, path_from_bytes};
 
-pub fn read_documents(reader: impl io::BufRead) -> impl Iterator<Item = Document> {
+/// Delimiter of paths in the input of most commands.
+pub const LINE_DELIMITER: u8 = b'\n';
+/// Delimiter of paths when using `-0`, like `find -print0` does. Allows paths
+/// containing line breaks.
+pub const NUL_DELIMITER: u8 = b'\0';
+
+/// Reads paths separated by `delimiter`, keeping their exact bytes.
+pub fn read_paths(reader: impl io::BufRead, delimiter: u8) -> impl Iterator<Item = PathBuf> {
     reader
-        .lines()
-        .map(|l| l.expect("Can't read line from input"))
-        .map(|l| Document::from_filename(&l))
+        .split(delimiter)
+        .map(|l| l.expect("Can't read path from input"))
+        .map(move |mut bytes| {
+            // Same as `BufRead::lines`.
+            if delimiter == LINE_DELIMITER && bytes.last() == Some(&b'\r') {
+                bytes.pop();
+            }
+
+            path_from_bytes(bytes)
+        })
+}
+
+pub fn read_documents(reader: impl io::BufRead, delimiter: u8) -> impl Iterator<Item = Document> {
+    read_paths(reader, delimiter).map(|p| Document::from_path(&p))
 }
 
-pub fn read_stdin_documents() -> impl Iterator<Item = Document> {
-    read_documents(io::stdin().lock())
+pub fn read_stdin_documents(delimiter: u8) -> impl Iterator<Item = Document> {
+    read_documents(io::stdin().lock(), delimiter)
+}
+
+/// Prepares each document to be matched against the library, see
+/// `Document::prepare`.
+pub fn with_library<'a>(
+    documents: impl Iterator<Item = Document> + 'a,
+    library: &'a LabelLibrary,
+) -> impl Iterator<Item = Document> + 'a {
+    documents.map(|mut document| {
+        document.prepare(library);
+        document
+    })
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -34,19 +65,16 @@ fn map_sources_to_target_folder(
 fn map_input_sources_to_target_folder(
     reader: impl io::BufRead,
     target_folder: PathBuf,
+    delimiter: u8,
 ) -> impl Iterator<Item = SourceTargetPair> {
-    let sources = reader
-        .lines()
-        .map(|l| l.expect("Can't read line from input"))
-        .map(|l| PathBuf::from(l));
-
-    map_sources_to_target_folder(sources, target_folder)
+    map_sources_to_target_folder(read_paths(reader, delimiter), target_folder)
 }
 
 pub fn map_stdin_sources_to_target_folder(
     target_folder: PathBuf,
+    delimiter: u8,
 ) -> impl Iterator<Item = SourceTargetPair> {
-    map_input_sources_to_target_folder(io::stdin().lock(), target_folder)
+    map_input_sources_to_target_folder(io::stdin().lock(), target_folder, delimiter)
 }
 
 #[cfg(test)]
@@ -59,25 +87,45 @@ mod tests {
     fn read_documents_works() {
         let input = "a b c fn file1.ext
         the path/to/la_la-la fn file2.ext";
-        let documents: Vec<_> = read_documents(input.as_bytes()).collect();
+        let documents: Vec<_> = read_documents(input.as_bytes(), LINE_DELIMITER).collect();
         assert_eq!(
             documents,
             vec![
                 Document {
-                    path: "a b c fn file1.ext".to_string(),
+                    path: "a b c fn file1.ext".into(),
                     name: "file1.ext".to_string(),
                     labels: LabelSet::from(["a", "b", "c"]),
+                    derived: LabelSet::empty(),
+                    metadata: Default::default(),
                 },
                 Document {
                     // TODO: Should this be trimmed by Document?
-                    path: "        the path/to/la_la-la fn file2.ext".to_string(),
+                    path: "        the path/to/la_la-la fn file2.ext".into(),
                     name: "file2.ext".to_string(),
                     labels: LabelSet::from(["la_la-la"]),
+                    derived: LabelSet::empty(),
+                    metadata: Default::default(),
                 },
             ]
         )
     }
 
+    #[test]
+    fn read_paths_works_with_nul_delimiters() {
+        let input = b"a\nb fn c\0d fn e\r\0";
+        let paths: Vec<_> = read_paths(&input[..], NUL_DELIMITER).collect();
+
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("a\nb fn c"), PathBuf::from("d fn e\r")]
+        );
+
+        let input = b"a\r\nb\n";
+        let paths: Vec<_> = read_paths(&input[..], LINE_DELIMITER).collect();
+
+        assert_eq!(paths, vec![PathBuf::from("a"), PathBuf::from("b")]);
+    }
+
     #[test]
     fn map_input_sources_to_target_folder_works() {
         let input = "a b c fn file1.ext
@@ -85,9 +133,12 @@ mod tests {
 
         let target_folder = Path::new("target_folder");
 
-        let pairs: Vec<_> =
-            map_input_sources_to_target_folder(input.as_bytes(), target_folder.to_path_buf())
-                .collect();
+        let pairs: Vec<_> = map_input_sources_to_target_folder(
+            input.as_bytes(),
+            target_folder.to_path_buf(),
+            LINE_DELIMITER,
+        )
+        .collect();
 
         let source_1 = Path::new("a b c fn file1.ext");
         let source_2 = Path::new("        the path/to/la_la-la fn file2.ext");
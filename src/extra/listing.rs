@@ -0,0 +1,182 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use crate::core::document::Document;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// Name without labels.
+    Name,
+    Path,
+    /// Labels of the document, written in the filename or derived by rules.
+    LabelCount,
+    /// Modification time, files without one first.
+    Mtime,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(SortKey::Name),
+            "path" => Ok(SortKey::Path),
+            "label-count" => Ok(SortKey::LabelCount),
+            "mtime" => Ok(SortKey::Mtime),
+            _ => Err(format!(
+                "Unknown sort key \"{}\", expected one of: name, path, label-count, mtime",
+                s
+            )),
+        }
+    }
+}
+
+impl SortKey {
+    fn compare(&self, a: &Document, b: &Document) -> Ordering {
+        match self {
+            SortKey::Name => a.name.cmp(&b.name),
+            SortKey::Path => a.path.cmp(&b.path),
+            SortKey::LabelCount => a.labels.inner_set().len().cmp(&b.labels.inner_set().len()),
+            SortKey::Mtime => {
+                let modified = |d: &Document| d.metadata().and_then(|m| m.modified);
+                modified(a).cmp(&modified(b))
+            }
+        }
+    }
+}
+
+/// How to present a list of documents. Applied in the same order as the
+/// fields.
+#[derive(Debug, Clone, Default)]
+pub struct ListingOptions {
+    /// Documents keep their order when missing or equal by the key.
+    pub sort: Option<SortKey>,
+    pub reverse: bool,
+    /// Keeps only the first document of each name.
+    pub unique_name: bool,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+impl ListingOptions {
+    /// Returns `true` if every document must be read before the first one can
+    /// be emitted.
+    pub fn is_buffered(&self) -> bool {
+        self.sort.is_some() || self.reverse
+    }
+}
+
+/// Applies the options lazily, unless sorting or reversing needs the whole
+/// list first.
+pub fn apply_listing<'a, D>(
+    documents: impl Iterator<Item = D> + 'a,
+    options: &ListingOptions,
+) -> impl Iterator<Item = D> + 'a
+where
+    D: Borrow<Document> + 'a,
+{
+    let mut documents: Box<dyn Iterator<Item = D> + 'a> = Box::new(documents);
+
+    if options.is_buffered() {
+        let mut all: Vec<D> = documents.collect();
+
+        if let Some(key) = options.sort {
+            all.sort_by(|a, b| key.compare(a.borrow(), b.borrow()));
+        }
+
+        if options.reverse {
+            all.reverse();
+        }
+
+        documents = Box::new(all.into_iter());
+    }
+
+    let unique_name = options.unique_name;
+    let mut seen = HashSet::new();
+
+    documents
+        .filter(move |d| !unique_name || seen.insert(d.borrow().name.clone()))
+        .skip(options.offset)
+        .take(options.limit.unwrap_or(usize::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(documents: &[&Document]) -> Vec<String> {
+        documents
+            .iter()
+            .map(|d| d.path.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn apply_listing_works() {
+        let documents = [
+            Document::from_filename("b/x y fn 2.txt"),
+            Document::from_filename("a/x fn 3.txt"),
+            Document::from_filename("c/x y z fn 1.txt"),
+            Document::from_filename("d/2.txt"),
+        ];
+        let documents: Vec<&Document> = documents.iter().collect();
+
+        let list = |options: ListingOptions| {
+            paths(&apply_listing(documents.iter().copied(), &options).collect::<Vec<_>>())
+        };
+
+        assert_eq!(
+            list(ListingOptions {
+                sort: Some(SortKey::Name),
+                ..Default::default()
+            }),
+            vec![
+                "c/x y z fn 1.txt",
+                "b/x y fn 2.txt",
+                "d/2.txt",
+                "a/x fn 3.txt"
+            ]
+        );
+
+        assert_eq!(
+            list(ListingOptions {
+                sort: Some(SortKey::LabelCount),
+                reverse: true,
+                ..Default::default()
+            }),
+            vec![
+                "c/x y z fn 1.txt",
+                "b/x y fn 2.txt",
+                "a/x fn 3.txt",
+                "d/2.txt"
+            ]
+        );
+
+        assert_eq!(
+            list(ListingOptions {
+                sort: Some(SortKey::Path),
+                unique_name: true,
+                offset: 1,
+                limit: Some(2),
+                ..Default::default()
+            }),
+            vec!["b/x y fn 2.txt", "c/x y z fn 1.txt"]
+        );
+
+        assert_eq!(
+            list(ListingOptions {
+                unique_name: true,
+                ..Default::default()
+            }),
+            vec!["b/x y fn 2.txt", "a/x fn 3.txt", "c/x y z fn 1.txt"]
+        );
+    }
+
+    #[test]
+    fn sort_key_from_str_works() {
+        assert_eq!("label-count".parse(), Ok(SortKey::LabelCount));
+        assert!("size".parse::<SortKey>().is_err());
+    }
+}
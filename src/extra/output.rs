@@ -0,0 +1,333 @@
+use serde::Serialize;
+use std::error::Error;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::core::document::Document;
+use crate::core::label::{LabelLibrary, LabelSet};
+use crate::utils::fs::path_to_bytes;
+
+pub const FORMATS: &[&str] = &["lines", "json", "jsonl", "csv", "tsv", "null-separated"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// One path per line.
+    #[default]
+    Lines,
+    /// A single array of records.
+    Json,
+    /// One record per line.
+    Jsonl,
+    Csv,
+    Tsv,
+    /// Paths separated by `\0`, for `xargs -0`.
+    NullSeparated,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lines" => Ok(Format::Lines),
+            "json" => Ok(Format::Json),
+            "jsonl" => Ok(Format::Jsonl),
+            "csv" => Ok(Format::Csv),
+            "tsv" => Ok(Format::Tsv),
+            "null-separated" => Ok(Format::NullSeparated),
+            _ => Err(format!(
+                "Unknown format \"{}\", expected one of: {}",
+                s,
+                FORMATS.join(", ")
+            )),
+        }
+    }
+}
+
+impl Format {
+    /// Format asked for with `--format`, if any. Otherwise `-0` means paths
+    /// separated by NUL.
+    pub fn from_args(format: Option<&str>, null: bool) -> Result<Self, String> {
+        match format {
+            Some(format) => format.parse(),
+            None if null => Ok(Format::NullSeparated),
+            None => Ok(Format::default()),
+        }
+    }
+
+    /// Returns `true` if the format includes labels, so they need to be
+    /// expanded.
+    pub fn has_labels(&self) -> bool {
+        !matches!(self, Format::Lines | Format::NullSeparated)
+    }
+}
+
+/// A document with its labels expanded, as written by `DocumentWriter`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentRecord<'a> {
+    #[serde(skip)]
+    pub document: &'a Document,
+    /// Replacing invalid UTF-8.
+    pub path: String,
+    /// Labels as written in the filename, not normalized.
+    pub labels: LabelSet,
+    pub name: &'a str,
+    pub derived: &'a LabelSet,
+    pub expanded: LabelSet,
+}
+
+impl<'a> DocumentRecord<'a> {
+    pub fn new(document: &'a Document, library: &LabelLibrary) -> Self {
+        let mut expanded = document.labels.clone();
+        expanded.expand_with(library);
+
+        Self {
+            document,
+            path: document.path.to_string_lossy().into_owned(),
+            labels: document.written_labels(),
+            name: &document.name,
+            derived: &document.derived,
+            expanded,
+        }
+    }
+}
+
+const TABLE_HEADER: [&str; 5] = ["path", "name", "labels", "derived", "expanded"];
+
+/// Writes documents in the given format, one at a time.
+pub struct DocumentWriter<W: Write> {
+    writer: W,
+    format: Format,
+    count: usize,
+}
+
+impl<W: Write> DocumentWriter<W> {
+    pub fn new(writer: W, format: Format) -> Self {
+        Self {
+            writer,
+            format,
+            count: 0,
+        }
+    }
+
+    pub fn write(&mut self, record: &DocumentRecord) -> Result<(), Box<dyn Error>> {
+        let first = self.count == 0;
+        self.count += 1;
+
+        match self.format {
+            Format::Lines => {
+                self.writer
+                    .write_all(&path_to_bytes(&record.document.path))?;
+                self.writer.write_all(b"\n")?;
+            }
+            Format::NullSeparated => {
+                self.writer
+                    .write_all(&path_to_bytes(&record.document.path))?;
+                self.writer.write_all(b"\0")?;
+            }
+            Format::Json => {
+                self.writer.write_all(if first { b"[\n" } else { b",\n" })?;
+                serde_json::to_writer(&mut self.writer, record)?;
+            }
+            Format::Jsonl => {
+                serde_json::to_writer(&mut self.writer, record)?;
+                writeln!(self.writer)?;
+            }
+            Format::Csv | Format::Tsv => {
+                if first {
+                    self.write_row(&TABLE_HEADER)?;
+                }
+
+                let labels = |set: &LabelSet| Vec::from(set.clone()).join(" ");
+
+                self.write_row(&[
+                    &record.path,
+                    record.name,
+                    &labels(&record.labels),
+                    &labels(record.derived),
+                    &labels(&record.expanded),
+                ])?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_row(&mut self, fields: &[&str]) -> io::Result<()> {
+        let (separator, escape): (&str, fn(&str) -> String) = match self.format {
+            Format::Tsv => ("\t", escape_tsv),
+            _ => (",", escape_csv),
+        };
+
+        let fields: Vec<String> = fields.iter().map(|f| escape(f)).collect();
+        writeln!(self.writer, "{}", fields.join(separator))
+    }
+
+    /// Writes what's needed to close the output, like the end of a JSON array.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.format == Format::Json {
+            let end: &[u8] = if self.count == 0 { b"[]\n" } else { b"\n]\n" };
+            self.writer.write_all(end)?;
+        }
+
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Quotes the field if needed, as described in RFC 4180.
+pub fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Escapes tabs, line breaks and backslashes, since TSV has no quoting.
+fn escape_tsv(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::label::tests::setup_library;
+
+    fn write_all(format: Format, filenames: &[&str]) -> String {
+        let library = setup_library();
+        let documents: Vec<Document> = filenames
+            .iter()
+            .map(|f| Document::from_filename(f))
+            .collect();
+
+        let mut writer = DocumentWriter::new(Vec::new(), format);
+
+        for document in documents.iter() {
+            writer
+                .write(&DocumentRecord::new(document, &library))
+                .unwrap();
+        }
+
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn from_args_works() {
+        assert_eq!(Format::from_args(None, false), Ok(Format::Lines));
+        assert_eq!(Format::from_args(None, true), Ok(Format::NullSeparated));
+        assert_eq!(Format::from_args(Some("csv"), true), Ok(Format::Csv));
+        assert!(Format::from_args(Some("xml"), false).is_err());
+    }
+
+    #[test]
+    fn json_works() {
+        let output = write_all(Format::Json, &["a/puppy fn x.jpg", "b"]);
+
+        let expected = r#"[
+{"path":"a/puppy fn x.jpg","labels":["puppy"],"name":"x.jpg","derived":[],"expanded":["adorable","cute","dog","kawaii","pet","puppy"]},
+{"path":"b","labels":[],"name":"b","derived":[],"expanded":[]}
+]
+"#;
+
+        assert_eq!(output, expected);
+        assert_eq!(write_all(Format::Json, &[]), "[]\n");
+    }
+
+    #[test]
+    fn labels_are_written_as_in_the_filename() {
+        use crate::core::label::normalize::Normalization;
+
+        let library = LabelLibrary::from_toml("[cat]")
+            .unwrap()
+            .normalized(Normalization {
+                case_folding: true,
+                ..Default::default()
+            })
+            .unwrap();
+
+        let mut document = Document::from_filename("Cat fn x");
+        document.prepare(&library);
+
+        let mut writer = DocumentWriter::new(Vec::new(), Format::Jsonl);
+        writer
+            .write(&DocumentRecord::new(&document, &library))
+            .unwrap();
+        let output = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+        assert_eq!(
+            output,
+            "{\"path\":\"Cat fn x\",\"labels\":[\"Cat\"],\"name\":\"x\",\"derived\":[],\"expanded\":[\"cat\"]}\n"
+        );
+    }
+
+    #[test]
+    fn jsonl_works() {
+        let output = write_all(Format::Jsonl, &["meme fn x", "y"]);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(r#"{"path":"meme fn x","labels":["meme"]"#));
+    }
+
+    #[test]
+    fn csv_and_tsv_work() {
+        let filenames = ["meme fn a, \"b\".txt", "c\td"];
+
+        assert_eq!(
+            write_all(Format::Csv, &filenames),
+            "path,name,labels,derived,expanded\n\
+             \"meme fn a, \"\"b\"\".txt\",\"a, \"\"b\"\".txt\",meme,,meme memes\n\
+             c\td,c\td,,,\n"
+        );
+
+        assert_eq!(
+            write_all(Format::Tsv, &filenames),
+            "path\tname\tlabels\tderived\texpanded\n\
+             meme fn a, \"b\".txt\ta, \"b\".txt\tmeme\t\tmeme memes\n\
+             c\\td\tc\\td\t\t\t\n"
+        );
+    }
+
+    #[test]
+    fn lines_and_null_separated_work() {
+        assert_eq!(write_all(Format::Lines, &["a fn b", "c"]), "a fn b\nc\n");
+        assert_eq!(
+            write_all(Format::NullSeparated, &["a fn b", "c"]),
+            "a fn b\0c\0"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn invalid_utf8_paths_work() {
+        use crate::utils::fs::path_from_bytes;
+
+        let library = setup_library();
+        let document = Document::from_path(&path_from_bytes(b"cat fn a\xff".to_vec()));
+        let record = DocumentRecord::new(&document, &library);
+
+        let write = |format| {
+            let mut writer = DocumentWriter::new(Vec::new(), format);
+            writer.write(&record).unwrap();
+            writer.finish().unwrap()
+        };
+
+        assert_eq!(write(Format::Lines), b"cat fn a\xff\n");
+        assert_eq!(write(Format::NullSeparated), b"cat fn a\xff\0");
+        assert!(String::from_utf8(write(Format::Jsonl))
+            .unwrap()
+            .starts_with("{\"path\":\"cat fn a\u{FFFD}\""));
+    }
+
+    #[test]
+    fn format_from_str_works() {
+        assert_eq!("jsonl".parse(), Ok(Format::Jsonl));
+        assert_eq!("null-separated".parse(), Ok(Format::NullSeparated));
+        assert!("xml".parse::<Format>().is_err());
+    }
+}
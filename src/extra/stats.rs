@@ -0,0 +1,165 @@
+//! Usage statistics of the labels of a set of documents.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+
+use serde::Serialize;
+
+use crate::core::document::Document;
+use crate::core::label::LabelLibrary;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LabelCount {
+    pub label: String,
+    pub known: bool,
+    /// Files with the label, or one of its aliases, written in the filename.
+    pub explicit: usize,
+    /// Files getting the label only from rules, implications or aliases.
+    pub implied: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Cooccurrence {
+    pub labels: [String; 2],
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LabelStats {
+    pub documents: usize,
+    /// Sorted by the files having the label, most used first.
+    pub labels: Vec<LabelCount>,
+    /// Labels of the library no file has, sorted by name.
+    pub unused: Vec<String>,
+    /// Pairs of labels written together in filenames, most frequent first.
+    pub cooccurrences: Vec<Cooccurrence>,
+}
+
+impl LabelStats {
+    /// Collects the statistics of the documents, keeping the `top` most
+    /// frequent co-occurrences. Labels are counted by their library name, so
+    /// aliases add to the label they belong to.
+    pub fn collect<I>(documents: I, library: &LabelLibrary, top: usize) -> Self
+    where
+        I: IntoIterator<Item = Document>,
+    {
+        let mut total = 0;
+        let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+        let mut pairs: HashMap<[String; 2], usize> = HashMap::new();
+
+        for document in documents {
+            total += 1;
+
+            let explicit: BTreeSet<&str> = document
+                .explicit_labels()
+                .map(|l| library.resolve(l))
+                .collect();
+
+            let mut expanded = document.labels.clone();
+            expanded.expand_with(library);
+            let expanded: BTreeSet<&str> = expanded.iter().map(|l| library.resolve(l)).collect();
+
+            for label in expanded.iter() {
+                let count = counts.entry(label.to_string()).or_default();
+
+                if explicit.contains(label) {
+                    count.0 += 1;
+                } else {
+                    count.1 += 1;
+                }
+            }
+
+            // Sorted sets give each pair in the same order.
+            let explicit: Vec<&str> = explicit.into_iter().collect();
+
+            for (i, a) in explicit.iter().enumerate() {
+                for b in explicit[i + 1..].iter() {
+                    *pairs.entry([a.to_string(), b.to_string()]).or_default() += 1;
+                }
+            }
+        }
+
+        let mut unused: Vec<String> = library
+            .label_names()
+            .into_iter()
+            .filter(|name| !counts.contains_key(*name))
+            .map(|name| name.to_string())
+            .collect();
+        unused.sort();
+
+        let mut labels: Vec<LabelCount> = counts
+            .into_iter()
+            .map(|(label, (explicit, implied))| LabelCount {
+                known: library.is_known(&label),
+                label,
+                explicit,
+                implied,
+            })
+            .collect();
+        labels.sort_by(|a, b| a.label.cmp(&b.label));
+        labels.sort_by_key(|c| Reverse(c.explicit + c.implied));
+
+        let mut cooccurrences: Vec<Cooccurrence> = pairs
+            .into_iter()
+            .map(|(labels, count)| Cooccurrence { labels, count })
+            .collect();
+        cooccurrences.sort_by(|a, b| a.labels.cmp(&b.labels));
+        cooccurrences.sort_by_key(|c| Reverse(c.count));
+        cooccurrences.truncate(top);
+
+        Self {
+            documents: total,
+            labels,
+            unused,
+            cooccurrences,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::label::tests::setup_library;
+
+    #[test]
+    fn collect_works() {
+        let library = setup_library();
+        let documents = [
+            "kitty meme fn a.jpg",
+            "cat meme fn b.jpg",
+            "tiger zzz fn c.jpg",
+        ]
+        .map(Document::from_filename);
+
+        let stats = LabelStats::collect(documents, &library, 1);
+
+        assert_eq!(stats.documents, 3);
+        assert_eq!(
+            stats.labels[0],
+            LabelCount {
+                label: "cat".to_string(),
+                known: true,
+                explicit: 2,
+                implied: 1,
+            }
+        );
+        assert_eq!(
+            stats.labels.iter().find(|c| c.label == "zzz"),
+            Some(&LabelCount {
+                label: "zzz".to_string(),
+                known: false,
+                explicit: 1,
+                implied: 0,
+            })
+        );
+        assert!(stats.unused.contains(&"dog".to_string()));
+        assert!(!stats.unused.contains(&"cute".to_string()));
+        assert_eq!(
+            stats.cooccurrences,
+            vec![Cooccurrence {
+                labels: ["cat".to_string(), "meme".to_string()],
+                count: 2,
+            }]
+        );
+    }
+}
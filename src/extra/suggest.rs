@@ -0,0 +1,361 @@
+//! Label suggestions for documents, learned from the labels already written
+//! in the filenames of other documents.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::core::document::{Document, FILENAME_LABELS_DELIMITER};
+use crate::core::label::LabelLibrary;
+
+/// Confidence given to a known label appearing as a word of the name.
+const NAME_MATCH_CONFIDENCE: f64 = 0.5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub label: String,
+    /// Between 0 and 1.
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SuggestOptions {
+    pub min_confidence: f64,
+    pub limit: usize,
+}
+
+type Counts = HashMap<String, usize>;
+
+/// How often labels are written in filenames next to each word of the names
+/// and in each directory. Only documents with labels are counted.
+#[derive(Debug, Default)]
+pub struct Model {
+    tokens: HashMap<String, (usize, Counts)>,
+    dirs: HashMap<PathBuf, (usize, Counts)>,
+}
+
+/// Lowercase words of the name, leaving out the extension, single characters
+/// and numbers.
+pub fn tokenize(name: &str) -> BTreeSet<String> {
+    let stem = match name.split_once('.') {
+        Some(("", _)) | None => name,
+        Some((stem, _)) => stem,
+    };
+
+    stem.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() > 1 && !t.chars().all(|c| c.is_numeric()))
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+/// Labels written in the filename by their library name, leaving out the
+/// ones derived by file rules.
+fn written_labels<'a>(document: &'a Document, library: &'a LabelLibrary) -> BTreeSet<&'a str> {
+    document
+        .explicit_labels()
+        .map(|l| library.resolve(l))
+        .collect()
+}
+
+fn dir_of(document: &Document) -> PathBuf {
+    document
+        .path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+/// Share of the `total` documents having the label, one more document is
+/// assumed without it so that a single example is not enough for certainty.
+fn ratio(counts: &(usize, Counts), label: &str, total: usize) -> f64 {
+    let count = counts.1.get(label).copied().unwrap_or(0);
+    count as f64 / (total + 1) as f64
+}
+
+impl Model {
+    pub fn train(documents: &[Document], library: &LabelLibrary) -> Self {
+        let mut model = Self::default();
+
+        for document in documents {
+            let labels = written_labels(document, library);
+
+            if labels.is_empty() {
+                continue;
+            }
+
+            let add = |entry: &mut (usize, Counts)| {
+                entry.0 += 1;
+                for label in labels.iter() {
+                    *entry.1.entry(label.to_string()).or_default() += 1;
+                }
+            };
+
+            for token in tokenize(&document.name) {
+                add(model.tokens.entry(token).or_default());
+            }
+
+            add(model.dirs.entry(dir_of(document)).or_default());
+        }
+
+        model
+    }
+
+    /// Suggests labels the document doesn't have yet, most confident first.
+    /// Labels conflicting with the ones it has, or with a more confident
+    /// suggestion, are left out.
+    ///
+    /// The document is expected to be one of the trained ones, its own labels
+    /// are left out of the statistics so it doesn't back itself.
+    pub fn suggest(
+        &self,
+        document: &Document,
+        library: &LabelLibrary,
+        options: &SuggestOptions,
+    ) -> Vec<Suggestion> {
+        let own = usize::from(!written_labels(document, library).is_empty());
+
+        let mut present = document.labels.clone();
+        present.expand_with(library);
+        let present: BTreeSet<&str> = present.iter().map(|l| library.resolve(l)).collect();
+
+        let tokens = tokenize(&document.name);
+        let dir = self.dirs.get(&dir_of(document));
+
+        let mut candidates: BTreeSet<&str> = BTreeSet::new();
+        for token in tokens.iter() {
+            if let Some(label) = library.resolve_known(token) {
+                candidates.insert(label);
+            }
+            if let Some((_, labels)) = self.tokens.get(token) {
+                candidates.extend(labels.keys().map(|l| l.as_str()));
+            }
+        }
+        if let Some((_, labels)) = dir {
+            candidates.extend(labels.keys().map(|l| l.as_str()));
+        }
+
+        let conflicting: BTreeSet<&str> = present
+            .iter()
+            .flat_map(|label| library.conflicts(label))
+            .collect();
+
+        let mut suggestions: Vec<Suggestion> = candidates
+            .into_iter()
+            .filter(|label| !present.contains(label) && !conflicting.contains(label))
+            .map(|label| {
+                let mut evidence: Vec<f64> = tokens
+                    .iter()
+                    .filter_map(|t| self.tokens.get(t))
+                    .map(|counts| ratio(counts, label, counts.0 - own))
+                    .collect();
+
+                if let Some(dir) = dir {
+                    evidence.push(ratio(dir, label, dir.0 - own));
+                }
+
+                if tokens
+                    .iter()
+                    .any(|t| library.resolve_known(t) == Some(label))
+                {
+                    evidence.push(NAME_MATCH_CONFIDENCE);
+                }
+
+                // Any of the sources may be right on its own.
+                let missed: f64 = evidence.iter().map(|e| 1.0 - e).product();
+
+                Suggestion {
+                    label: label.to_string(),
+                    confidence: 1.0 - missed,
+                }
+            })
+            .filter(|s| s.confidence >= options.min_confidence)
+            .collect();
+
+        suggestions.sort_by(|a, b| {
+            b.confidence
+                .partial_cmp(&a.confidence)
+                .unwrap_or(Ordering::Equal)
+        });
+
+        let mut accepted: Vec<Suggestion> = Vec::new();
+
+        for suggestion in suggestions {
+            if accepted.len() == options.limit {
+                break;
+            }
+
+            let conflicts = library.conflicts(&suggestion.label);
+            if !accepted
+                .iter()
+                .any(|a| conflicts.contains(&a.label.as_str()))
+            {
+                accepted.push(suggestion);
+            }
+        }
+
+        accepted
+    }
+}
+
+/// Path of the document with the labels added to the ones written in its
+/// filename, or `None` if the filename isn't valid UTF-8.
+pub fn with_labels(path: &Path, labels: &[&str]) -> Option<PathBuf> {
+    let filename = path.file_name()?.to_str()?;
+    let labels = labels.join(" ");
+
+    let filename = match filename.split_once(FILENAME_LABELS_DELIMITER) {
+        Some((written, name)) => format!(
+            "{} {}{}{}",
+            written.trim_end(),
+            labels,
+            FILENAME_LABELS_DELIMITER,
+            name
+        ),
+        None => format!("{}{}{}", labels, FILENAME_LABELS_DELIMITER, filename),
+    };
+
+    Some(path.with_file_name(filename.trim_start()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::label::tests::setup_library;
+
+    const OPTIONS: SuggestOptions = SuggestOptions {
+        min_confidence: 0.3,
+        limit: 3,
+    };
+
+    #[test]
+    fn tokenize_works() {
+        assert_eq!(
+            tokenize("My_Cat-photo 2 2021.tar.gz"),
+            BTreeSet::from(["my".to_string(), "cat".to_string(), "photo".to_string()])
+        );
+        assert_eq!(tokenize(".hidden"), BTreeSet::from(["hidden".to_string()]));
+    }
+
+    #[test]
+    fn suggest_works() {
+        let library = setup_library();
+        let documents = [
+            "pics/meme fn funny cat.jpg",
+            "pics/meme fn funny dog.jpg",
+            "pics/cat fn sleeping.jpg",
+            "pics/funny kitty.jpg",
+            "other/tiger fn big.jpg",
+        ]
+        .map(Document::from_filename);
+        let model = Model::train(&documents, &library);
+
+        let suggestions = model.suggest(&documents[3], &library, &OPTIONS);
+        let labels: Vec<&str> = suggestions.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(labels, vec!["meme", "cat"]);
+
+        // Already implied by the written label.
+        let suggestions = model.suggest(&documents[4], &library, &OPTIONS);
+        assert!(suggestions.iter().all(|s| s.label != "cat"));
+
+        // A label in the name is suggested even without examples.
+        let suggestions = model.suggest(&documents[0], &library, &OPTIONS);
+        assert_eq!(suggestions[0].label, "cat");
+    }
+
+    #[test]
+    fn suggest_leaves_out_conflicting_labels() {
+        let library = LabelLibrary::from_toml(
+            r#"
+            [draft]
+            conflicts_with = ["final"]
+
+            [final]
+
+            [review]
+            conflicts_with = ["approved"]
+
+            [approved]
+        "#,
+        )
+        .unwrap();
+        let documents = [
+            "docs/final review fn a.txt",
+            "docs/final review fn b.txt",
+            "docs/final approved fn c.txt",
+            "docs/draft fn d.txt",
+            "docs/e.txt",
+        ]
+        .map(Document::from_filename);
+        let model = Model::train(&documents, &library);
+
+        // Has `draft`, which conflicts with the most frequent label.
+        let suggestions = model.suggest(&documents[3], &library, &OPTIONS);
+        assert!(suggestions.iter().all(|s| s.label != "final"));
+
+        // `review` is more frequent than `approved`, which conflicts with it.
+        let labels: Vec<String> = model
+            .suggest(&documents[4], &library, &OPTIONS)
+            .into_iter()
+            .map(|s| s.label)
+            .collect();
+        assert_eq!(labels, vec!["final", "review"]);
+    }
+
+    #[test]
+    fn renaming_keeps_written_labels_and_leaves_out_derived_ones() {
+        use crate::core::label::normalize::Normalization;
+
+        let library = LabelLibrary::from_toml(
+            r#"
+            [cat]
+            [meme]
+
+            [[rules]]
+            path = "pics/**"
+            implies = ["photo"]
+        "#,
+        )
+        .unwrap()
+        .normalized(Normalization {
+            case_folding: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut documents = [
+            "pics/Cat Meme fn a.jpg",
+            "pics/cat meme fn b.jpg",
+            "pics/Cat fn c.jpg",
+        ]
+        .map(Document::from_filename);
+        for document in documents.iter_mut() {
+            document.prepare(&library);
+        }
+
+        let model = Model::train(&documents, &library);
+        let suggestions = model.suggest(&documents[2], &library, &OPTIONS);
+        let labels: Vec<&str> = suggestions.iter().map(|s| s.label.as_str()).collect();
+
+        assert_eq!(labels, vec!["meme"]);
+        assert_eq!(
+            with_labels(&documents[2].path, &labels),
+            Some(PathBuf::from("pics/Cat meme fn c.jpg"))
+        );
+    }
+
+    #[test]
+    fn with_labels_works() {
+        assert_eq!(
+            with_labels(Path::new("a/cat fn b.jpg"), &["meme", "pet"]),
+            Some(PathBuf::from("a/cat meme pet fn b.jpg"))
+        );
+        assert_eq!(
+            with_labels(Path::new("a/b.jpg"), &["meme"]),
+            Some(PathBuf::from("a/meme fn b.jpg"))
+        );
+        assert_eq!(
+            with_labels(Path::new(" fn b.jpg"), &["meme"]),
+            Some(PathBuf::from("meme fn b.jpg"))
+        );
+    }
+}
@@ -0,0 +1,413 @@
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Paths buffered between the workers and the consumer. Workers wait once it's
+/// full, so a slow consumer doesn't make the walk hold every path in memory.
+const CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, Default)]
+pub struct WalkOptions {
+    /// Maximum depth of the emitted entries. Direct children of the root have
+    /// a depth of 1. `None` means unlimited.
+    pub max_depth: Option<usize>,
+    /// Descend into symlinks pointing to directories. Loops are detected and
+    /// not followed.
+    pub follow_links: bool,
+    /// Don't descend into directories living on a different file system than
+    /// the root. Only has an effect on unix.
+    pub same_file_system: bool,
+    /// Entries with any of these file names are neither emitted nor descended.
+    pub exclude_names: Vec<OsString>,
+    /// Number of worker threads. `0` means one per available CPU.
+    pub threads: usize,
+}
+
+/// Identifies a directory to detect symlink loops and file system boundaries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DirId {
+    dev: u64,
+    ino: u64,
+}
+
+#[cfg(unix)]
+fn dir_id(path: &Path) -> Option<DirId> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs::metadata(path).ok()?;
+    Some(DirId {
+        dev: metadata.dev(),
+        ino: metadata.ino(),
+    })
+}
+
+#[cfg(not(unix))]
+fn dir_id(_path: &Path) -> Option<DirId> {
+    None
+}
+
+struct Job {
+    dir: PathBuf,
+    depth: usize,
+    ancestors: Vec<DirId>,
+}
+
+struct Queue {
+    jobs: VecDeque<Job>,
+    /// Jobs taken by a worker but not finished yet.
+    active: usize,
+}
+
+struct Shared {
+    options: WalkOptions,
+    root_dev: Option<u64>,
+    queue: Mutex<Queue>,
+    ready: Condvar,
+    stopped: AtomicBool,
+}
+
+/// Iterator over the entries below a root directory, traversed in parallel.
+///
+/// The order of the emitted paths is not deterministic.
+pub struct Walk {
+    /// Only `None` while dropping.
+    receiver: Option<Receiver<PathBuf>>,
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl Iterator for Walk {
+    type Item = PathBuf;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.as_ref()?.recv().ok()
+    }
+}
+
+impl Drop for Walk {
+    fn drop(&mut self) {
+        self.shared.stopped.store(true, Ordering::Relaxed);
+        self.shared.ready.notify_all();
+
+        // Workers blocked on a full channel fail to send and stop.
+        self.receiver.take();
+
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Walks every entry below `root` (excluding `root` itself) yielding the
+/// entry paths joined to `root`.
+///
+/// Entries that can't be read are silently skipped.
+pub fn walk(root: &Path, options: WalkOptions) -> Walk {
+    let threads = match options.threads {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    };
+
+    let root_id = dir_id(root);
+    let root_dev = if options.same_file_system {
+        root_id.map(|id| id.dev)
+    } else {
+        None
+    };
+
+    let mut jobs = VecDeque::new();
+
+    if options.max_depth != Some(0) {
+        jobs.push_back(Job {
+            dir: root.to_path_buf(),
+            depth: 0,
+            ancestors: root_id.into_iter().collect(),
+        });
+    }
+
+    let shared = Arc::new(Shared {
+        options,
+        root_dev,
+        queue: Mutex::new(Queue { jobs, active: 0 }),
+        ready: Condvar::new(),
+        stopped: AtomicBool::new(false),
+    });
+
+    let (sender, receiver) = mpsc::sync_channel(CHANNEL_CAPACITY);
+
+    let workers = (0..threads)
+        .map(|_| {
+            let shared = Arc::clone(&shared);
+            let sender = sender.clone();
+            thread::spawn(move || work(&shared, &sender))
+        })
+        .collect();
+
+    Walk {
+        receiver: Some(receiver),
+        shared,
+        workers,
+    }
+}
+
+fn work(shared: &Shared, sender: &SyncSender<PathBuf>) {
+    while let Some(job) = next_job(shared) {
+        let children = read_job(shared, &job, sender);
+
+        let mut queue = shared.queue.lock().unwrap();
+        queue.active -= 1;
+        queue.jobs.extend(children);
+        drop(queue);
+
+        shared.ready.notify_all();
+    }
+}
+
+fn next_job(shared: &Shared) -> Option<Job> {
+    let mut queue = shared.queue.lock().unwrap();
+
+    loop {
+        if shared.stopped.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        if let Some(job) = queue.jobs.pop_front() {
+            queue.active += 1;
+            return Some(job);
+        }
+
+        // Nothing queued and nobody can queue more, the walk is complete.
+        if queue.active == 0 {
+            return None;
+        }
+
+        queue = shared.ready.wait(queue).unwrap();
+    }
+}
+
+/// Emits the entries of the job directory and returns the subdirectories that
+/// must be traversed next.
+fn read_job(shared: &Shared, job: &Job, sender: &SyncSender<PathBuf>) -> Vec<Job> {
+    let options = &shared.options;
+    let mut children = Vec::new();
+
+    let entries = match fs::read_dir(&job.dir) {
+        Ok(entries) => entries,
+        Err(_) => return children,
+    };
+
+    let depth = job.depth + 1;
+    let descend = options.max_depth.is_none_or(|max| depth < max);
+
+    for entry in entries.flatten() {
+        if options.exclude_names.contains(&entry.file_name()) {
+            continue;
+        }
+
+        let path = entry.path();
+
+        if sender.send(path.clone()).is_err() {
+            // The receiving side is gone, nobody cares about the rest.
+            shared.stopped.store(true, Ordering::Relaxed);
+            return Vec::new();
+        }
+
+        if !descend {
+            continue;
+        }
+
+        // `DirEntry::file_type` does not require an extra stat on most platforms.
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(_) => continue,
+        };
+
+        let is_dir = if file_type.is_symlink() {
+            options.follow_links && path.is_dir()
+        } else {
+            file_type.is_dir()
+        };
+
+        if !is_dir {
+            continue;
+        }
+
+        if let Some(job) = child_job(shared, job, path, depth) {
+            children.push(job);
+        }
+    }
+
+    children
+}
+
+fn child_job(shared: &Shared, parent: &Job, dir: PathBuf, depth: usize) -> Option<Job> {
+    // Identity is only needed for loop detection and file system boundaries.
+    if !shared.options.follow_links && shared.root_dev.is_none() {
+        return Some(Job {
+            dir,
+            depth,
+            ancestors: Vec::new(),
+        });
+    }
+
+    let id = dir_id(&dir);
+
+    if let (Some(root_dev), Some(id)) = (shared.root_dev, id) {
+        if id.dev != root_dev {
+            return None;
+        }
+    }
+
+    let mut ancestors = Vec::new();
+
+    if shared.options.follow_links {
+        if let Some(id) = id {
+            if parent.ancestors.contains(&id) {
+                return None;
+            }
+
+            ancestors = parent.ancestors.clone();
+            ancestors.push(id);
+        }
+    }
+
+    Some(Job {
+        dir,
+        depth,
+        ancestors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a fresh directory tree in the temp dir for the given test.
+    fn fixture(test_name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "fileclass-walk-{}-{}",
+            test_name,
+            std::process::id()
+        ));
+
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("fileclass")).unwrap();
+        fs::create_dir_all(root.join("files/sub")).unwrap();
+        fs::write(root.join("fileclass/labels.toml"), "").unwrap();
+        fs::write(root.join("files/a.txt"), "").unwrap();
+        fs::write(root.join("files/sub/b.txt"), "").unwrap();
+
+        root
+    }
+
+    fn walk_relative(root: &Path, options: WalkOptions) -> Vec<PathBuf> {
+        let mut paths: Vec<_> = walk(root, options)
+            .map(|p| p.strip_prefix(root).unwrap().to_path_buf())
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn walk_works() {
+        let root = fixture("works");
+        let paths = walk_relative(&root, WalkOptions::default());
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("fileclass"),
+                PathBuf::from("fileclass/labels.toml"),
+                PathBuf::from("files"),
+                PathBuf::from("files/a.txt"),
+                PathBuf::from("files/sub"),
+                PathBuf::from("files/sub/b.txt"),
+            ]
+        );
+    }
+
+    #[test]
+    fn walk_works_with_max_depth_and_exclusions() {
+        let options = WalkOptions {
+            max_depth: Some(2),
+            exclude_names: vec!["fileclass".into()],
+            threads: 2,
+            ..WalkOptions::default()
+        };
+
+        let root = fixture("max-depth");
+        let paths = walk_relative(&root, options);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("files"),
+                PathBuf::from("files/a.txt"),
+                PathBuf::from("files/sub"),
+            ]
+        );
+    }
+
+    #[test]
+    fn walk_works_with_zero_max_depth() {
+        let options = WalkOptions {
+            max_depth: Some(0),
+            ..WalkOptions::default()
+        };
+
+        assert!(walk_relative(Path::new("test_dir"), options).is_empty());
+    }
+
+    #[test]
+    fn walk_stops_when_dropped_with_a_full_channel() {
+        let root = fixture("full-channel");
+        for i in 0..CHANNEL_CAPACITY * 2 {
+            fs::write(root.join(format!("files/{}.txt", i)), "").unwrap();
+        }
+
+        let options = WalkOptions {
+            threads: 2,
+            ..WalkOptions::default()
+        };
+
+        // Would hang if the workers kept waiting for room in the channel.
+        let first: Vec<PathBuf> = walk(&root, options).take(1).collect();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(first.len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn walk_detects_symlink_loops() {
+        let root = fixture("loops");
+        std::os::unix::fs::symlink("..", root.join("files/sub/loop")).unwrap();
+
+        let options = WalkOptions {
+            follow_links: true,
+            exclude_names: vec!["fileclass".into()],
+            ..WalkOptions::default()
+        };
+
+        let paths = walk_relative(&root, options);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("files"),
+                PathBuf::from("files/a.txt"),
+                PathBuf::from("files/sub"),
+                PathBuf::from("files/sub/b.txt"),
+                PathBuf::from("files/sub/loop"),
+            ]
+        );
+    }
+}
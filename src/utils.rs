@@ -1 +1,4 @@
 pub mod fs;
+pub mod glob;
+pub mod regex_cache;
+pub mod text;
@@ -1,15 +1,66 @@
+use std::borrow::Cow;
+use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
 
-pub fn get_prefix(path: &Path) -> &str {
-    let file_name = path.file_name().unwrap().to_str().unwrap();
-    let first_dot_index = file_name.find('.').unwrap_or(file_name.len());
-    &file_name[..first_dot_index]
+/// Builds a path from the raw bytes read from an input. They are kept as they
+/// are on Unix, elsewhere invalid UTF-8 is replaced.
+pub fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    #[cfg(unix)]
+    {
+        use std::ffi::OsString;
+        use std::os::unix::ffi::OsStringExt;
+
+        PathBuf::from(OsString::from_vec(bytes))
+    }
+
+    #[cfg(not(unix))]
+    {
+        PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+/// Raw bytes of a path, the inverse of `path_from_bytes`.
+pub fn path_to_bytes(path: &Path) -> Cow<'_, [u8]> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+
+        Cow::Borrowed(path.as_os_str().as_bytes())
+    }
+
+    #[cfg(not(unix))]
+    {
+        match path.to_string_lossy() {
+            Cow::Borrowed(s) => Cow::Borrowed(s.as_bytes()),
+            Cow::Owned(s) => Cow::Owned(s.into_bytes()),
+        }
+    }
+}
+
+/// Splits the file name at its first dot, which stays in the suffix. Works with
+/// names that aren't valid UTF-8.
+fn split_file_name(path: &Path) -> (&OsStr, &OsStr) {
+    let file_name = path.file_name().unwrap();
+    let bytes = file_name.as_encoded_bytes();
+    let first_dot_index = bytes.iter().position(|&b| b == b'.').unwrap_or(bytes.len());
+    let (prefix, suffix) = bytes.split_at(first_dot_index);
+
+    // SAFETY: Both halves come from an `OsStr` split right before an ASCII
+    // character, or at its end.
+    unsafe {
+        (
+            OsStr::from_encoded_bytes_unchecked(prefix),
+            OsStr::from_encoded_bytes_unchecked(suffix),
+        )
+    }
+}
+
+pub fn get_prefix(path: &Path) -> &OsStr {
+    split_file_name(path).0
 }
 
-pub fn get_suffix(path: &Path) -> &str {
-    let file_name = path.file_name().unwrap().to_str().unwrap();
-    let first_dot_index = file_name.find('.').unwrap_or(file_name.len());
-    &file_name[first_dot_index..]
+pub fn get_suffix(path: &Path) -> &OsStr {
+    split_file_name(path).1
 }
 
 pub fn get_unique_target(source_path: &Path, target_dir: &Path) -> PathBuf {
@@ -17,9 +68,9 @@ pub fn get_unique_target(source_path: &Path, target_dir: &Path) -> PathBuf {
     let mut index = 1;
 
     while target_file.exists() {
-        let file_name = get_prefix(source_path);
-        let file_ext = get_suffix(source_path);
-        let link_name = format!("{} ({}){}", file_name, index, file_ext);
+        let mut link_name = get_prefix(source_path).to_os_string();
+        link_name.push(format!(" ({})", index));
+        link_name.push(get_suffix(source_path));
         target_file = target_dir.join(link_name);
         index += 1;
     }
@@ -51,6 +102,27 @@ mod tests {
         assert_eq!(get_suffix(Path::new("test_dir/files/a")), "");
     }
 
+    #[test]
+    fn path_bytes_round_trip() {
+        let bytes = b"dir/a\nb fn c.txt".to_vec();
+        let path = path_from_bytes(bytes.clone());
+
+        assert_eq!(path, Path::new("dir/a\nb fn c.txt"));
+        assert_eq!(path_to_bytes(&path), bytes.as_slice());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn get_prefix_and_suffix_work_with_invalid_utf8() {
+        let path = path_from_bytes(b"dir/a\xff.b\xfe.txt".to_vec());
+
+        assert_eq!(path_to_bytes(Path::new(get_prefix(&path))), &b"a\xff"[..]);
+        assert_eq!(
+            path_to_bytes(Path::new(get_suffix(&path))),
+            &b".b\xfe.txt"[..]
+        );
+    }
+
     #[test]
     fn test_get_unique_target() {
         let source_path = Path::new("test_dir/files/a.txt");
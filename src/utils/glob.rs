@@ -0,0 +1,187 @@
+//! Minimal glob matching.
+//!
+//! Supported syntax:
+//! - `*` matches any sequence of characters except `/`.
+//! - `**` matches any sequence of characters including `/`. When followed by
+//!   `/` it also matches zero directories, so `**/a` matches `a`.
+//! - `?` matches a single character except `/`.
+//! - `[abc]`, `[a-z]` and `[!abc]` match a single character from (or not from)
+//!   the given set.
+//! - `\` escapes the next character.
+
+use std::borrow::Cow;
+
+/// Returns `true` if the pattern contains any unescaped special character.
+pub fn is_glob(pattern: &str) -> bool {
+    let mut chars = pattern.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '*' | '?' | '[' => return true,
+            _ => {}
+        }
+    }
+
+    false
+}
+
+/// Removes the escaping backslashes of a pattern without special characters.
+pub fn unescape(pattern: &str) -> Cow<'_, str> {
+    if !pattern.contains('\\') {
+        return Cow::Borrowed(pattern);
+    }
+
+    let mut result = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => result.extend(chars.next()),
+            _ => result.push(c),
+        }
+    }
+
+    Cow::Owned(result)
+}
+
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    match_from(&pattern, &text)
+}
+
+fn match_from(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', '/', rest @ ..] => {
+            match_from(rest, text)
+                || (0..text.len())
+                    .filter(|&i| text[i] == '/')
+                    .any(|i| match_from(rest, &text[i + 1..]))
+        }
+        ['*', '*', rest @ ..] => (0..=text.len()).any(|i| match_from(rest, &text[i..])),
+        ['*', rest @ ..] => {
+            let max = text.iter().position(|&c| c == '/').unwrap_or(text.len());
+            (0..=max).any(|i| match_from(rest, &text[i..]))
+        }
+        ['?', rest @ ..] => match text {
+            [c, text_rest @ ..] if *c != '/' => match_from(rest, text_rest),
+            _ => false,
+        },
+        ['[', rest @ ..] => match (parse_class(rest), text) {
+            (Some((matches, class_len)), [c, text_rest @ ..]) => {
+                matches(*c) && match_from(&rest[class_len..], text_rest)
+            }
+            // An unclosed class is matched literally.
+            (None, [c, text_rest @ ..]) => *c == '[' && match_from(rest, text_rest),
+            _ => false,
+        },
+        ['\\', c, rest @ ..] => match text {
+            [t, text_rest @ ..] if t == c => match_from(rest, text_rest),
+            _ => false,
+        },
+        [c, rest @ ..] => match text {
+            [t, text_rest @ ..] if t == c => match_from(rest, text_rest),
+            _ => false,
+        },
+    }
+}
+
+/// Parses a character class following a `[`, returning a predicate and the
+/// number of pattern characters consumed, including the closing `]`.
+fn parse_class(pattern: &[char]) -> Option<(impl Fn(char) -> bool + '_, usize)> {
+    let (negated, start) = match pattern.first() {
+        Some('!') | Some('^') => (true, 1),
+        _ => (false, 0),
+    };
+
+    // A `]` right at the start is part of the set.
+    let end = pattern[start..]
+        .iter()
+        .skip(1)
+        .position(|&c| c == ']')
+        .map(|i| start + i + 1)?;
+
+    let set = &pattern[start..end];
+
+    let matches = move |c: char| {
+        let mut found = false;
+        let mut i = 0;
+
+        while i < set.len() {
+            if i + 2 < set.len() && set[i + 1] == '-' {
+                found |= set[i] <= c && c <= set[i + 2];
+                i += 3;
+            } else {
+                found |= set[i] == c;
+                i += 1;
+            }
+        }
+
+        found != negated
+    };
+
+    Some((matches, end + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_match_works_with_stars() {
+        assert!(glob_match("*.pdf", "file.pdf"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("*.pdf", "file.pdf.txt"));
+        assert!(!glob_match("*.pdf", "dir/file.pdf"));
+        assert!(glob_match("proj_*", "proj_alpha"));
+        assert!(!glob_match("proj_*", "project"));
+    }
+
+    #[test]
+    fn glob_match_works_with_double_stars() {
+        assert!(glob_match("projects/**", "projects/a/b.txt"));
+        assert!(glob_match("**/*.pdf", "a/b/c.pdf"));
+        assert!(glob_match("**/*.pdf", "c.pdf"));
+        assert!(glob_match("a/**/b", "a/b"));
+        assert!(glob_match("a/**/b", "a/x/y/b"));
+        assert!(!glob_match("projects/**", "other/a"));
+    }
+
+    #[test]
+    fn glob_match_works_with_question_marks_and_classes() {
+        assert!(glob_match("year_202?", "year_2023"));
+        assert!(!glob_match("year_202?", "year_20234"));
+        assert!(glob_match("[abc]at", "bat"));
+        assert!(glob_match("[a-c]at", "cat"));
+        assert!(!glob_match("[!a-c]at", "cat"));
+        assert!(glob_match("[!a-c]at", "rat"));
+        assert!(glob_match("[]]", "]"));
+        assert!(glob_match("a[", "a["));
+    }
+
+    #[test]
+    fn glob_match_works_with_escapes() {
+        assert!(glob_match(r"a\*", "a*"));
+        assert!(!glob_match(r"a\*", "ab"));
+        assert!(glob_match(r"\[x]", "[x]"));
+    }
+
+    #[test]
+    fn is_glob_works() {
+        assert!(is_glob("a*"));
+        assert!(is_glob("a?"));
+        assert!(is_glob("[ab]"));
+        assert!(!is_glob("abc"));
+        assert!(!is_glob(r"a\*"));
+    }
+
+    #[test]
+    fn unescape_works() {
+        assert_eq!(unescape(r"a\*b\\"), r"a*b\");
+        assert_eq!(unescape("abc"), "abc");
+    }
+}
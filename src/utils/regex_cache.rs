@@ -0,0 +1,25 @@
+use regex::Regex;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+thread_local! {
+    /// Compiled patterns, since the same pattern is checked against every document.
+    static CACHE: RefCell<HashMap<String, Option<Regex>>> = RefCell::new(HashMap::new());
+}
+
+/// Matches the text against a regex, compiling it only once per thread.
+///
+/// Invalid regexes never match.
+pub fn is_match(pattern: &str, text: &str) -> bool {
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let regex = cache
+            .entry(pattern.to_string())
+            .or_insert_with(|| Regex::new(pattern).ok());
+
+        match regex {
+            Some(regex) => regex.is_match(text),
+            None => false,
+        }
+    })
+}
@@ -0,0 +1,85 @@
+/// Edit distance between two strings, counted in characters, where
+/// insertions, deletions, substitutions and transpositions of adjacent
+/// characters cost 1 (optimal string alignment distance).
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // distances[i][j] is the distance between the first i chars of `a` and
+    // the first j chars of `b`.
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+/// Candidates close enough to `target` to be considered typos of it, closest
+/// first.
+pub fn closest_matches<'a>(
+    target: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+    limit: usize,
+) -> Vec<&'a str> {
+    let max_distance = (target.chars().count() / 3).clamp(1, 3);
+
+    let mut matches: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .filter(|c| *c != target)
+        .map(|c| (edit_distance(target, c), c))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+
+    matches.sort();
+    matches.dedup();
+    matches.truncate(limit);
+
+    matches.into_iter().map(|(_, c)| c).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance_works() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("cat", "cat"), 0);
+        assert_eq!(edit_distance("catt", "cat"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("ñandú", "nandu"), 2);
+        assert_eq!(edit_distance("dgo", "dog"), 1);
+    }
+
+    #[test]
+    fn closest_matches_works() {
+        let candidates = ["cat", "cats", "car", "dog", "catt"];
+
+        assert_eq!(closest_matches("catt", candidates, 3), vec!["cat", "cats"]);
+        assert_eq!(closest_matches("dgo", candidates, 3), vec!["dog"]);
+        assert!(closest_matches("zebra", candidates, 3).is_empty());
+    }
+}